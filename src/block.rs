@@ -0,0 +1,150 @@
+
+use llvm_sys::prelude::*;
+use llvm_sys::core::*;
+
+use std::ffi::{CString,CStr};
+use std::marker::PhantomData;
+
+use super::context::Brand;
+use super::value::Value;
+use super::function::Function;
+use super::module::Module;
+
+/// Abstraction around llvm::BasicBlock
+///
+/// A straight line run of instructions ending
+/// in a terminator. Blocks belong to a function,
+/// this is just a handle to one.
+#[derive(Copy,Clone)]
+pub struct BasicBlock<'m, 'ctx: 'm> {
+    data: LLVMBasicBlockRef,
+    module: PhantomData<&'m Module<'ctx>>
+}
+impl<'m, 'ctx> BasicBlock<'m, 'ctx> {
+
+    /// Get the name of this item
+    pub fn get_name(&self) -> CString {
+        unsafe {
+            CStr::from_ptr(LLVMGetBasicBlockName(self.data)).to_owned()
+        }
+    }
+
+    /// The function this block belongs to
+    pub fn parent(&self) -> Function<'m, 'ctx> {
+        unsafe {
+            Function::from_raw(LLVMGetBasicBlockParent(self.data))
+        }
+    }
+
+    /// The instruction ending this block
+    ///
+    /// Returns `None` if the block isn't finished yet
+    pub fn terminator(&self) -> Option<Value<'ctx>> {
+        unsafe {
+            let ptr = LLVMGetBasicBlockTerminator(self.data);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(Value::from_raw(ptr))
+            }
+        }
+    }
+
+    /// Iterate over the instructions in this block
+    pub fn instructions(&self) -> Instructions<'ctx> {
+        unsafe {
+            Instructions {
+                next: LLVMGetFirstInstruction(self.data),
+                context: PhantomData
+            }
+        }
+    }
+
+    /// Remove this block from its function and free it
+    ///
+    /// #Safety:
+    ///
+    /// `BasicBlock` is `Copy`, so other handles to
+    /// it may still exist. None of them, nor its
+    /// instructions may be used afterwards, and no
+    /// branch may still target it.
+    pub unsafe fn delete(self) {
+        LLVMDeleteBasicBlock(self.data);
+    }
+
+    /// The block as a plain value
+    pub fn as_value(&self) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMBasicBlockAsValue(self.data))
+        }
+    }
+
+    /// Method internal to the library.
+    ///
+    /// The caller picks `'m`, it must not outlive
+    /// the module holding the block.
+    pub unsafe fn from_raw(data: LLVMBasicBlockRef) -> BasicBlock<'m, 'ctx> {
+        BasicBlock {
+            data: data,
+            module: PhantomData
+        }
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&self) -> LLVMBasicBlockRef {
+        self.data
+    }
+}
+
+/// Iterator over the basic blocks in a `Function`
+///
+/// Returned by `Function::basic_blocks`
+pub struct BasicBlocks<'m, 'ctx: 'm> {
+    next: LLVMBasicBlockRef,
+    module: PhantomData<&'m Module<'ctx>>
+}
+impl<'m, 'ctx> BasicBlocks<'m, 'ctx> {
+
+    /// Method internal to the library.
+    pub unsafe fn from_raw(first: LLVMBasicBlockRef) -> BasicBlocks<'m, 'ctx> {
+        BasicBlocks {
+            next: first,
+            module: PhantomData
+        }
+    }
+}
+impl<'m, 'ctx> Iterator for BasicBlocks<'m, 'ctx> {
+    type Item = BasicBlock<'m, 'ctx>;
+    fn next(&mut self) -> Option<BasicBlock<'m, 'ctx>> {
+        if self.next.is_null() {
+            return None;
+        }
+        unsafe {
+            let bb = BasicBlock::from_raw(self.next);
+            self.next = LLVMGetNextBasicBlock(self.next);
+            Some(bb)
+        }
+    }
+}
+
+/// Iterator over the instructions in a `BasicBlock`
+///
+/// Returned by `BasicBlock::instructions`
+pub struct Instructions<'ctx> {
+    next: LLVMValueRef,
+    context: Brand<'ctx>
+}
+impl<'ctx> Iterator for Instructions<'ctx> {
+    type Item = Value<'ctx>;
+    fn next(&mut self) -> Option<Value<'ctx>> {
+        if self.next.is_null() {
+            return None;
+        }
+        unsafe {
+            let v = Value::from_raw(self.next);
+            self.next = LLVMGetNextInstruction(self.next);
+            Some(v)
+        }
+    }
+}
@@ -1,22 +1,24 @@
 use super::llvm_sys::*;
 use super::llvm_sys::prelude::*;
 use super::llvm_sys::core::*;
-use super::llvm_sys::analysis::*;
 use super::llvm_sys::bit_reader::*;
+use super::llvm_sys::ir_reader::*;
 
 use std::ffi::{CString,CStr};
 use std::os::raw::c_char;
 use std::io::prelude::*;
 use std::fs::{OpenOptions,File};
-use std::io;
 use std::path::Path;
+use std::ops::Deref;
+use std::slice;
+use std::io;
+use std::fmt;
+use std::error;
 
-use super::Buffers;
+use super::{Buffers,take_message,path_to_cstring};
 use super::module::Module;
-
-const NULLPTR: &'static str = "
-Buffer name value has a null ptr
-";
+use super::context::Context;
+use super::error::{Error,Result};
 
 /// Abstraction around llvm::MemoryBuffer
 ///
@@ -48,36 +50,37 @@ impl Buffer {
     ///
     /// This will zero the buffer before giving
     /// it to the LLVM.
-    pub fn with_capacity<S: Into<Vec<u8>>>(size: usize, name: S) -> Buffer {
-        let mut v = Vec::<u8>::with_capacity(size);
-        for _ in 0..size {
+    pub fn with_capacity<S: Into<Vec<u8>>>(size: usize, name: S) -> Result<Buffer> {
+        //one extra zero so the LLVM sees a null terminator
+        let mut v = Vec::<u8>::with_capacity(size + 1);
+        for _ in 0..size + 1 {
             v.push(0u8);
         }
-        let name = CString::new(name).expect(NULLPTR);
+        let name = CString::new(name)?;
         unsafe{
             let b_ptr = v.as_ptr() as *const c_char;
             let name_ptr = name.as_ptr() as *const c_char;
-            let buf = LLVMCreateMemoryBufferWithMemoryRange(b_ptr, size, name_ptr, 0);
-            Buffer {
+            let buf = LLVMCreateMemoryBufferWithMemoryRange(b_ptr, size, name_ptr, 1);
+            Ok(Buffer {
                 data: buf,
                 buffers: vec![Buffers::A(name), Buffers::B(v)]
-            }
+            })
         }
     }
 
     /// Create a new memory buffer by copying
     /// from another buffer.
-    pub fn copy_from<S: Into<Vec<u8>>>(buf: &[u8], name: S) -> Buffer {
-        let name = CString::new(name).expect(NULLPTR);
+    pub fn copy_from<S: Into<Vec<u8>>>(buf: &[u8], name: S) -> Result<Buffer> {
+        let name = CString::new(name)?;
         unsafe{
             let len = buf.len();
             let ptr = buf.as_ptr() as *const c_char;
             let name_ptr = name.as_ptr() as *const c_char;
             let buf = LLVMCreateMemoryBufferWithMemoryRangeCopy(ptr, len, name_ptr);
-            Buffer {
+            Ok(Buffer {
                 data: buf,
                 buffers: vec![Buffers::A(name)]
-            }
+            })
         }
     }
   
@@ -86,26 +89,66 @@ impl Buffer {
     ///
     /// If this item contains LLVM-IR this function will
     /// attempt to parse it and convert it into
-    /// an LLVM Module
-    pub fn parse_ir(self) -> Result<Module,(Buffer,CString)> {
+    /// an LLVM Module within the global context.
+    pub fn parse_ir(self) -> Result<Module<'static>> {
+        unsafe {
+            self.parse_ir_raw(LLVMGetGlobalContext())
+        }
+    }
+
+    /// Parse IR within a Context
+    ///
+    /// Same as `parse_ir` but the resulting Module
+    /// belongs to `ctx` rather then the global context.
+    pub fn parse_ir_in<'ctx>(self, ctx: &'ctx Context) -> Result<Module<'ctx>> {
+        unsafe {
+            self.parse_ir_raw(ctx.inner())
+        }
+    }
+
+    #[allow(deprecated)]
+    unsafe fn parse_ir_raw<'ctx>(self, ctx: LLVMContextRef) -> Result<Module<'ctx>> {
         use std::mem;
-        
+
+        let mut s = self;
+        let mut modu: LLVMModuleRef = mem::zeroed();
+        let mut err: *mut c_char = mem::zeroed();
+        let flag = LLVMParseBitcodeInContext(ctx, s.data, &mut modu, &mut err);
+        if flag != 0 {
+            Err(Error::Parse(ParseError::from_message(take_message(err))))
+        } else {
+            let mut v = Vec::<Buffers>::with_capacity(0);
+            mem::swap(&mut v, &mut s.buffers);
+            Ok(Module::from_raw(modu, v))
+        }
+    }
+
+    /// Parse Assembly
+    ///
+    /// Parses either textual `.ll` IR or bitcode
+    /// into a Module within `ctx`.
+    ///
+    /// The LLVM consumes the buffer while parsing
+    /// so it isn't returned on failure.
+    pub fn parse_assembly<'ctx>(self, ctx: &'ctx Context) -> Result<Module<'ctx>> {
+        use std::mem;
+
         let mut s = self;
-        let mut modu = Module::new(s.get_name().to_bytes());
         let mut v = Vec::<Buffers>::with_capacity(0);
         mem::swap(&mut v, &mut s.buffers);
         unsafe {
+            let data = s.data;
+            mem::forget(s);
+            let mut modu: LLVMModuleRef = mem::zeroed();
             let mut err: *mut c_char = mem::zeroed();
-            let flag = LLVMParseBitcode(s.data,modu.raw_module(),&mut err);
+            let flag = LLVMParseIRInContext(ctx.inner(), data, &mut modu, &mut err);
             if flag != 0 {
-                mem::swap(&mut v, &mut s.buffers);
-                Err((s,CString::from_raw(err)))
+                Err(Error::Parse(ParseError::from_message(take_message(err))))
             } else {
-                modu.append_buffers(&mut v);
-                Ok(modu)
+                Ok(Module::from_raw(modu, v))
             }
         }
-    } 
+    }
 
     /// From Rust Buffer
     ///
@@ -113,18 +156,23 @@ impl Buffer {
     /// and it will expose that buffer to the LLVM
     /// as a MemBuffer
     ///
-    /// This provides a zero copy way to load data
-    pub fn from_owned<S: Into<Vec<u8>>>(buf: Vec<u8>, name: S) -> Buffer {
-        let name = CString::new(name).expect(NULLPTR);
+    /// This provides a zero copy way to load data.
+    /// A null byte is pushed onto the end of `buf`,
+    /// outside the buffer's contents, as the LLVM's
+    /// IR lexer reads one byte past the end.
+    pub fn from_owned<S: Into<Vec<u8>>>(buf: Vec<u8>, name: S) -> Result<Buffer> {
+        let name = CString::new(name)?;
+        let mut buf = buf;
+        let len = buf.len();
+        buf.push(0u8);
         unsafe{
-            let len = buf.len();
             let ptr = buf.as_ptr() as *const c_char;
             let n_ptr = name.as_ptr() as *const c_char;
-            let llvm = LLVMCreateMemoryBufferWithMemoryRange(ptr, len, n_ptr, 0);
-            Buffer {
+            let llvm = LLVMCreateMemoryBufferWithMemoryRange(ptr, len, n_ptr, 1);
+            Ok(Buffer {
                 data: llvm,
                 buffers: vec![Buffers::A(name),Buffers::B(buf)]
-            }
+            })
         }
     }
 
@@ -134,47 +182,142 @@ impl Buffer {
     /// No name is required as the name of the
     /// file is used as it's name.
     ///
-    /// #Panic:
-    ///
-    /// This function will panic if path has
-    /// no name
-    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Buffer>  {
-        let name = path
-            .as_ref()
-            .file_name()
-            .expect("Path has no file")
-            .to_os_string()
-            .into_string()
-            .expect("Path isn't utf-8 parsable");
+    /// Fails if the path has no file name, or
+    /// it isn't utf-8.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Buffer>  {
+        let name = match path.as_ref().file_name().and_then(|x| x.to_str()) {
+            Some(x) => x.to_string(),
+            None => return Err(Error::Io(io::Error::new(io::ErrorKind::InvalidInput, "Path has no utf-8 file name")))
+        };
         let mut f: File = OpenOptions::new()
             .write(false)
             .create(false)
             .read(true)
             .open(path)?;
         let size = f.metadata()?.len() as usize;
-        let mut buff = Vec::with_capacity(size);
+        let mut buff = Vec::with_capacity(size + 1);
         let _ = f.read_to_end(&mut buff)?;
-        Ok(Buffer::from_owned(buff,name))
+        Buffer::from_owned(buff,name)
+    }
+
+    /// Read From File with the LLVM
+    ///
+    /// The LLVM memory maps large files rather
+    /// then reading them. The path is used as
+    /// the buffer's name.
+    ///
+    /// The file is opened with `std` first, so a
+    /// missing or unreadable file fails with the
+    /// same `io::ErrorKind` as `from_file`.
+    pub fn from_path_llvm<P: AsRef<Path>>(path: P) -> Result<Buffer> {
+        use std::mem;
+
+        let _ = File::open(path.as_ref())?;
+        let path = path_to_cstring(path.as_ref())?;
+        unsafe {
+            let mut buf: LLVMMemoryBufferRef = mem::zeroed();
+            let mut err: *mut c_char = mem::zeroed();
+            let flag = LLVMCreateMemoryBufferWithContentsOfFile(path.as_ptr(), &mut buf, &mut err);
+            if flag != 0 {
+                return Err(Error::Io(io::Error::new(io::ErrorKind::Other, take_message(err))));
+            }
+            Ok(Buffer::from_raw(buf, vec![Buffers::A(path)]))
+        }
+    }
+
+    /// Read all of stdin
+    ///
+    /// The buffer is named `<stdin>`
+    ///
+    /// The LLVM reads file descriptor 0 directly,
+    /// so anything already buffered by
+    /// `std::io::stdin()` is skipped.
+    pub fn from_stdin() -> Result<Buffer> {
+        use std::mem;
+
+        unsafe {
+            let mut buf: LLVMMemoryBufferRef = mem::zeroed();
+            let mut err: *mut c_char = mem::zeroed();
+            let flag = LLVMCreateMemoryBufferWithSTDIN(&mut buf, &mut err);
+            if flag != 0 {
+                return Err(Error::Io(io::Error::new(io::ErrorKind::Other, take_message(err))));
+            }
+            let name = CString::new("<stdin>").unwrap();
+            Ok(Buffer::from_raw(buf, vec![Buffers::A(name)]))
+        }
     }
 
     /// Get the name of this item
-    pub fn get_name<'a>(&self) -> &'a CStr {
-        use std::mem::transmute;
-        
+    ///
+    /// Returns `None` if the buffer was built
+    /// with `from_raw` and no name was kept.
+    pub fn get_name(&self) -> Option<&CStr> {
+        self.buffers.iter()
+            .filter_map(|b| match *b {
+                Buffers::A(ref name) => Some(name.as_c_str()),
+                _ => None
+            })
+            .next()
+    }
+
+    /// The contents of this buffer
+    pub fn as_bytes(&self) -> &[u8] {
         unsafe {
-            let ptr = LLVMGetValueName(transmute(self.data));
-            let val: usize = transmute(ptr);
-            if val == 0 {
-                panic!("Module Name returned a null ptr");
+            let ptr = LLVMGetBufferStart(self.data);
+            let len = LLVMGetBufferSize(self.data);
+            if ptr.is_null() || len == 0 {
+                return &[];
             }
-            CStr::from_ptr(ptr)
+            slice::from_raw_parts(ptr as *const u8, len)
+        }
+    }
+
+    /// Size of this buffer in bytes
+    pub fn len(&self) -> usize {
+        unsafe {
+            LLVMGetBufferSize(self.data)
         }
     }
 
+    /// Is this buffer empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read the contents of this buffer with
+    /// `std::io::Read`
+    pub fn cursor(&self) -> io::Cursor<&[u8]> {
+        io::Cursor::new(self.as_bytes())
+    }
+
+    /// Write the contents of this buffer to a file
+    ///
+    /// The file is created, or truncated if it
+    /// already exists.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut f: File = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        f.write_all(self.as_bytes())?;
+        Ok(())
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&self) -> LLVMMemoryBufferRef {
+        self.data
+    }
+
     /// From raw
     ///
     /// unsafely buids this item from it's raw components. Primarily
     /// used for internal interfaces
+    ///
+    /// The byte after the end of `x` must be null,
+    /// or `parse_assembly` will read past it. Every
+    /// buffer the LLVM creates is.
     pub unsafe fn from_raw(x: LLVMMemoryBufferRef, buf: Vec<Buffers>) -> Buffer {
         Buffer {
             data: x,
@@ -183,4 +326,120 @@ impl Buffer {
     }
  
 }
+impl AsRef<[u8]> for Buffer {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+impl Deref for Buffer {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Error from parsing IR
+///
+/// The LLVM reports errors as a single message
+/// shaped like `name:line:column: error: message`.
+/// Fields are split out of it when present.
+#[derive(Clone,Debug)]
+pub struct ParseError {
+    /// Name of the buffer being parsed
+    pub buffer: Option<String>,
+    /// Line the error occured on, starting at 1
+    pub line: Option<u32>,
+    /// Column the error occured at, starting at 1
+    pub column: Option<u32>,
+    /// The full message the LLVM gave
+    pub message: String
+}
+impl ParseError {
+
+    /// Split location information out of an LLVM message
+    pub fn from_message(message: String) -> ParseError {
+        let mut err = ParseError {
+            buffer: None,
+            line: None,
+            column: None,
+            message: message
+        };
+        let location = match err.message.lines().next() {
+            Some(line) => match line.find(": error:") {
+                Some(idx) => line[..idx].to_string(),
+                None => return err
+            },
+            None => return err
+        };
+        let mut parts = location.rsplitn(3, ':');
+        let column = parts.next().and_then(|x| x.parse::<u32>().ok());
+        let line = parts.next().and_then(|x| x.parse::<u32>().ok());
+        match (line, column, parts.next()) {
+            (Some(line), Some(column), Some(name)) => {
+                err.buffer = Some(name.to_string());
+                err.line = Some(line);
+                err.column = Some(column);
+            },
+            _ => {
+                err.buffer = Some(location);
+            }
+        };
+        err
+    }
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message.trim_end())
+    }
+}
+impl error::Error for ParseError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    #[test]
+    fn parse_assembly_from_owned() {
+        let ctx = Context::new();
+        let ir = b"define i32 @main() {\n  ret i32 0\n}\n";
+        //leave garbage in the spare capacity after the text
+        let mut v = Vec::with_capacity(ir.len() + 64);
+        v.extend_from_slice(ir);
+        v.extend_from_slice(&[b'x'; 64]);
+        v.truncate(ir.len());
+        let buf = Buffer::from_owned(v, "main.ll").unwrap();
+        assert_eq!(buf.as_bytes(), &ir[..]);
+        let module = buf.parse_assembly(&ctx).unwrap();
+        assert!(module.get_function("main").is_some());
+    }
+
+    #[test]
+    fn parse_error_location() {
+        let err = ParseError::from_message("main.ll:3:7: error: expected type\n  ret x\n      ^\n".to_string());
+        assert_eq!(err.buffer, Some("main.ll".to_string()));
+        assert_eq!(err.line, Some(3));
+        assert_eq!(err.column, Some(7));
+        assert_eq!(err.to_string(), "main.ll:3:7: error: expected type\n  ret x\n      ^");
+    }
+
+    #[test]
+    fn parse_error_name_with_colons() {
+        let err = ParseError::from_message("C:\\ir\\main.ll:12:1: error: unknown token".to_string());
+        assert_eq!(err.buffer, Some("C:\\ir\\main.ll".to_string()));
+        assert_eq!(err.line, Some(12));
+        assert_eq!(err.column, Some(1));
+    }
+
+    #[test]
+    fn parse_error_no_location() {
+        let err = ParseError::from_message("Invalid bitcode signature".to_string());
+        assert_eq!(err.buffer, None);
+        assert_eq!(err.line, None);
+        assert_eq!(err.column, None);
+        assert_eq!(err.message, "Invalid bitcode signature");
+    }
+}
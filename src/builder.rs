@@ -0,0 +1,458 @@
+
+use llvm_sys::*;
+use llvm_sys::prelude::*;
+use llvm_sys::core::*;
+
+use std::ffi::CString;
+use std::marker::PhantomData;
+
+use super::context::{Context,Brand};
+use super::value::{Value,raw_values};
+use super::types::Type;
+use super::block::BasicBlock;
+use super::error::Result;
+
+macro_rules! binop {
+    ($name: ident, $op: ident) => {
+        pub fn $name<S: AsRef<str>>(&self, lhs: Value<'ctx>, rhs: Value<'ctx>, name: S) -> Result<Value<'ctx>> {
+            let name = CString::new(name.as_ref())?;
+            unsafe {
+                Ok(Value::from_raw($op(self.data, lhs.inner(), rhs.inner(), name.as_ptr())))
+            }
+        }
+    }
+}
+
+macro_rules! unop {
+    ($name: ident, $op: ident) => {
+        pub fn $name<S: AsRef<str>>(&self, val: Value<'ctx>, name: S) -> Result<Value<'ctx>> {
+            let name = CString::new(name.as_ref())?;
+            unsafe {
+                Ok(Value::from_raw($op(self.data, val.inner(), name.as_ptr())))
+            }
+        }
+    }
+}
+
+macro_rules! cast {
+    ($name: ident, $op: ident) => {
+        pub fn $name<S: AsRef<str>>(&self, val: Value<'ctx>, ty: Type<'ctx>, name: S) -> Result<Value<'ctx>> {
+            let name = CString::new(name.as_ref())?;
+            unsafe {
+                Ok(Value::from_raw($op(self.data, val.inner(), ty.inner(), name.as_ptr())))
+            }
+        }
+    }
+}
+
+/// Abstraction around llvm::IRBuilder
+///
+/// The builder emits instructions at its current
+/// position. Position it inside a `BasicBlock`
+/// before building anything.
+pub struct Builder<'ctx> {
+    data: LLVMBuilderRef,
+    context: Brand<'ctx>
+}
+impl<'ctx> Drop for Builder<'ctx> {
+    fn drop(&mut self) {
+        unsafe {
+            LLVMDisposeBuilder(self.data);
+        }
+    }
+}
+impl Builder<'static> {
+
+    /// Create a new Builder within the global context
+    pub fn new() -> Builder<'static> {
+        unsafe {
+            Builder {
+                data: LLVMCreateBuilder(),
+                context: PhantomData
+            }
+        }
+    }
+}
+impl<'ctx> Builder<'ctx> {
+
+    /// Create a new Builder within a Context
+    pub fn new_in(ctx: &'ctx Context) -> Builder<'ctx> {
+        unsafe {
+            Builder {
+                data: LLVMCreateBuilderInContext(ctx.inner()),
+                context: PhantomData
+            }
+        }
+    }
+
+    /// Emit new instructions at the end of `block`
+    pub fn position_at_end(&mut self, block: BasicBlock<'_, 'ctx>) {
+        unsafe {
+            LLVMPositionBuilderAtEnd(self.data, block.inner());
+        }
+    }
+
+    /// Emit new instructions directly before `instr`
+    pub fn position_before(&mut self, instr: Value<'ctx>) {
+        unsafe {
+            LLVMPositionBuilderBefore(self.data, instr.inner());
+        }
+    }
+
+    /// The block instructions are currently being inserted into
+    ///
+    /// #Safety:
+    ///
+    /// The Builder doesn't borrow the module it is
+    /// building in, so the caller picks `'m`. It
+    /// must not outlive that module.
+    pub unsafe fn insert_block<'m>(&self) -> Option<BasicBlock<'m, 'ctx>> {
+        unsafe {
+            let ptr = LLVMGetInsertBlock(self.data);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(BasicBlock::from_raw(ptr))
+            }
+        }
+    }
+
+    /// Clear the insertion position
+    pub fn clear_position(&mut self) {
+        unsafe {
+            LLVMClearInsertionPosition(self.data);
+        }
+    }
+
+    binop!(build_add,LLVMBuildAdd);
+    binop!(build_nsw_add,LLVMBuildNSWAdd);
+    binop!(build_nuw_add,LLVMBuildNUWAdd);
+    binop!(build_fadd,LLVMBuildFAdd);
+    binop!(build_sub,LLVMBuildSub);
+    binop!(build_nsw_sub,LLVMBuildNSWSub);
+    binop!(build_nuw_sub,LLVMBuildNUWSub);
+    binop!(build_fsub,LLVMBuildFSub);
+    binop!(build_mul,LLVMBuildMul);
+    binop!(build_nsw_mul,LLVMBuildNSWMul);
+    binop!(build_nuw_mul,LLVMBuildNUWMul);
+    binop!(build_fmul,LLVMBuildFMul);
+    binop!(build_udiv,LLVMBuildUDiv);
+    binop!(build_sdiv,LLVMBuildSDiv);
+    binop!(build_exact_sdiv,LLVMBuildExactSDiv);
+    binop!(build_fdiv,LLVMBuildFDiv);
+    binop!(build_urem,LLVMBuildURem);
+    binop!(build_srem,LLVMBuildSRem);
+    binop!(build_frem,LLVMBuildFRem);
+    binop!(build_shl,LLVMBuildShl);
+    binop!(build_lshr,LLVMBuildLShr);
+    binop!(build_ashr,LLVMBuildAShr);
+    binop!(build_and,LLVMBuildAnd);
+    binop!(build_or,LLVMBuildOr);
+    binop!(build_xor,LLVMBuildXor);
+    unop!(build_neg,LLVMBuildNeg);
+    unop!(build_nsw_neg,LLVMBuildNSWNeg);
+    unop!(build_nuw_neg,LLVMBuildNUWNeg);
+    unop!(build_fneg,LLVMBuildFNeg);
+    unop!(build_not,LLVMBuildNot);
+
+    /// Integer comparison, yields an `i1`
+    pub fn build_icmp<S: AsRef<str>>(&self, op: IntPredicate, lhs: Value<'ctx>, rhs: Value<'ctx>, name: S) -> Result<Value<'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            Ok(Value::from_raw(LLVMBuildICmp(self.data, op.into(), lhs.inner(), rhs.inner(), name.as_ptr())))
+        }
+    }
+
+    /// Floating point comparison, yields an `i1`
+    pub fn build_fcmp<S: AsRef<str>>(&self, op: RealPredicate, lhs: Value<'ctx>, rhs: Value<'ctx>, name: S) -> Result<Value<'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            Ok(Value::from_raw(LLVMBuildFCmp(self.data, op.into(), lhs.inner(), rhs.inner(), name.as_ptr())))
+        }
+    }
+
+    /// Allocate a `ty` on the stack
+    pub fn build_alloca<S: AsRef<str>>(&self, ty: Type<'ctx>, name: S) -> Result<Value<'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            Ok(Value::from_raw(LLVMBuildAlloca(self.data, ty.inner(), name.as_ptr())))
+        }
+    }
+
+    /// Allocate `count` many `ty` on the stack
+    pub fn build_array_alloca<S: AsRef<str>>(&self, ty: Type<'ctx>, count: Value<'ctx>, name: S) -> Result<Value<'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            Ok(Value::from_raw(LLVMBuildArrayAlloca(self.data, ty.inner(), count.inner(), name.as_ptr())))
+        }
+    }
+
+    unop!(build_load,LLVMBuildLoad);
+
+    /// Store `val` to the address `ptr`
+    pub fn build_store(&self, val: Value<'ctx>, ptr: Value<'ctx>) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMBuildStore(self.data, val.inner(), ptr.inner()))
+        }
+    }
+
+    /// Get Element Pointer
+    pub fn build_gep<S: AsRef<str>>(&self, ptr: Value<'ctx>, indices: &[Value<'ctx>], name: S) -> Result<Value<'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        let mut indices = raw_values(indices);
+        unsafe {
+            let len = indices.len() as u32;
+            Ok(Value::from_raw(LLVMBuildGEP(self.data, ptr.inner(), indices.as_mut_ptr(), len, name.as_ptr())))
+        }
+    }
+
+    /// Get Element Pointer which is known to stay within
+    /// the bounds of the allocated object
+    pub fn build_inbounds_gep<S: AsRef<str>>(&self, ptr: Value<'ctx>, indices: &[Value<'ctx>], name: S) -> Result<Value<'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        let mut indices = raw_values(indices);
+        unsafe {
+            let len = indices.len() as u32;
+            Ok(Value::from_raw(LLVMBuildInBoundsGEP(self.data, ptr.inner(), indices.as_mut_ptr(), len, name.as_ptr())))
+        }
+    }
+
+    /// Get a pointer to field `idx` of the struct at `ptr`
+    pub fn build_struct_gep<S: AsRef<str>>(&self, ptr: Value<'ctx>, idx: u32, name: S) -> Result<Value<'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            Ok(Value::from_raw(LLVMBuildStructGEP(self.data, ptr.inner(), idx, name.as_ptr())))
+        }
+    }
+
+    cast!(build_trunc,LLVMBuildTrunc);
+    cast!(build_zext,LLVMBuildZExt);
+    cast!(build_sext,LLVMBuildSExt);
+    cast!(build_fptoui,LLVMBuildFPToUI);
+    cast!(build_fptosi,LLVMBuildFPToSI);
+    cast!(build_uitofp,LLVMBuildUIToFP);
+    cast!(build_sitofp,LLVMBuildSIToFP);
+    cast!(build_fptrunc,LLVMBuildFPTrunc);
+    cast!(build_fpext,LLVMBuildFPExt);
+    cast!(build_ptrtoint,LLVMBuildPtrToInt);
+    cast!(build_inttoptr,LLVMBuildIntToPtr);
+    cast!(build_bitcast,LLVMBuildBitCast);
+    cast!(build_addrspace_cast,LLVMBuildAddrSpaceCast);
+    cast!(build_pointer_cast,LLVMBuildPointerCast);
+    cast!(build_int_cast,LLVMBuildIntCast);
+    cast!(build_fp_cast,LLVMBuildFPCast);
+
+    /// Call `func` with `args`
+    pub fn build_call<S: AsRef<str>>(&self, func: Value<'ctx>, args: &[Value<'ctx>], name: S) -> Result<Value<'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        let mut args = raw_values(args);
+        unsafe {
+            let len = args.len() as u32;
+            Ok(Value::from_raw(LLVMBuildCall(self.data, func.inner(), args.as_mut_ptr(), len, name.as_ptr())))
+        }
+    }
+
+    /// Pick `then` if `cond` is true, otherwise `other`
+    pub fn build_select<S: AsRef<str>>(&self, cond: Value<'ctx>, then: Value<'ctx>, other: Value<'ctx>, name: S) -> Result<Value<'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            Ok(Value::from_raw(LLVMBuildSelect(self.data, cond.inner(), then.inner(), other.inner(), name.as_ptr())))
+        }
+    }
+
+    /// Create an empty phi node of type `ty`
+    ///
+    /// Incoming values are added to the returned
+    /// `PhiNode`.
+    pub fn build_phi<S: AsRef<str>>(&self, ty: Type<'ctx>, name: S) -> Result<PhiNode<'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            Ok(PhiNode {
+                data: Value::from_raw(LLVMBuildPhi(self.data, ty.inner(), name.as_ptr()))
+            })
+        }
+    }
+
+    /// Unconditional branch
+    pub fn build_br(&self, dest: BasicBlock<'_, 'ctx>) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMBuildBr(self.data, dest.inner()))
+        }
+    }
+
+    /// Conditional branch on an `i1`
+    pub fn build_cond_br(&self, cond: Value<'ctx>, then: BasicBlock<'_, 'ctx>, other: BasicBlock<'_, 'ctx>) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMBuildCondBr(self.data, cond.inner(), then.inner(), other.inner()))
+        }
+    }
+
+    /// Switch on `val` jumping to `default` if no case matches
+    ///
+    /// `num_cases` is only a hint for how much space
+    /// to reserve. Cases are added to the returned
+    /// `Switch`.
+    pub fn build_switch(&self, val: Value<'ctx>, default: BasicBlock<'_, 'ctx>, num_cases: u32) -> Switch<'ctx> {
+        unsafe {
+            Switch {
+                data: Value::from_raw(LLVMBuildSwitch(self.data, val.inner(), default.inner(), num_cases))
+            }
+        }
+    }
+
+    /// Return `val` from the current function
+    pub fn build_ret(&self, val: Value<'ctx>) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMBuildRet(self.data, val.inner()))
+        }
+    }
+
+    /// Return from a function returning `void`
+    pub fn build_ret_void(&self) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMBuildRetVoid(self.data))
+        }
+    }
+
+    /// Mark the current position as unreachable
+    pub fn build_unreachable(&self) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMBuildUnreachable(self.data))
+        }
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&mut self) -> LLVMBuilderRef {
+        self.data
+    }
+}
+
+/// A `phi` instruction
+///
+/// Returned by `Builder::build_phi`
+#[derive(Copy,Clone)]
+pub struct PhiNode<'ctx> {
+    data: Value<'ctx>
+}
+impl<'ctx> PhiNode<'ctx> {
+
+    /// Add incoming values and the blocks they come from
+    pub fn add_incoming(&self, incoming: &[(Value<'ctx>, BasicBlock<'_, 'ctx>)]) {
+        let mut vals: Vec<LLVMValueRef> = incoming.iter().map(|x| unsafe { x.0.inner() }).collect();
+        let mut blocks: Vec<LLVMBasicBlockRef> = incoming.iter().map(|x| unsafe { x.1.inner() }).collect();
+        unsafe {
+            LLVMAddIncoming(self.data.inner(), vals.as_mut_ptr(), blocks.as_mut_ptr(), vals.len() as u32);
+        }
+    }
+
+    /// The phi node as a plain value
+    pub fn as_value(&self) -> Value<'ctx> {
+        self.data
+    }
+}
+
+/// A `switch` instruction
+///
+/// Returned by `Builder::build_switch`
+#[derive(Copy,Clone)]
+pub struct Switch<'ctx> {
+    data: Value<'ctx>
+}
+impl<'ctx> Switch<'ctx> {
+
+    /// Jump to `dest` when the switched on value equals `val`
+    ///
+    /// `val` must be a constant integer
+    pub fn add_case(&self, val: Value<'ctx>, dest: BasicBlock<'_, 'ctx>) {
+        unsafe {
+            LLVMAddCase(self.data.inner(), val.inner(), dest.inner());
+        }
+    }
+
+    /// The switch as a plain value
+    pub fn as_value(&self) -> Value<'ctx> {
+        self.data
+    }
+}
+
+/// Integer comparison predicates
+///
+/// `U` prefixed predicates are unsigned, `S`
+/// prefixed ones are signed.
+#[derive(Copy,Clone,Debug)]
+pub enum IntPredicate {
+    EQ,
+    NE,
+    UGT,
+    UGE,
+    ULT,
+    ULE,
+    SGT,
+    SGE,
+    SLT,
+    SLE
+}
+impl Into<LLVMIntPredicate> for IntPredicate {
+    fn into(self) -> LLVMIntPredicate {
+        match self {
+            IntPredicate::EQ => LLVMIntPredicate::LLVMIntEQ,
+            IntPredicate::NE => LLVMIntPredicate::LLVMIntNE,
+            IntPredicate::UGT => LLVMIntPredicate::LLVMIntUGT,
+            IntPredicate::UGE => LLVMIntPredicate::LLVMIntUGE,
+            IntPredicate::ULT => LLVMIntPredicate::LLVMIntULT,
+            IntPredicate::ULE => LLVMIntPredicate::LLVMIntULE,
+            IntPredicate::SGT => LLVMIntPredicate::LLVMIntSGT,
+            IntPredicate::SGE => LLVMIntPredicate::LLVMIntSGE,
+            IntPredicate::SLT => LLVMIntPredicate::LLVMIntSLT,
+            IntPredicate::SLE => LLVMIntPredicate::LLVMIntSLE
+        }
+    }
+}
+
+/// Floating point comparison predicates
+///
+/// `O` prefixed predicates are ordered (false if
+/// either side is NaN), `U` prefixed ones are
+/// unordered (true if either side is NaN).
+#[derive(Copy,Clone,Debug)]
+pub enum RealPredicate {
+    False,
+    OEQ,
+    OGT,
+    OGE,
+    OLT,
+    OLE,
+    ONE,
+    ORD,
+    UNO,
+    UEQ,
+    UGT,
+    UGE,
+    ULT,
+    ULE,
+    UNE,
+    True
+}
+impl Into<LLVMRealPredicate> for RealPredicate {
+    fn into(self) -> LLVMRealPredicate {
+        match self {
+            RealPredicate::False => LLVMRealPredicate::LLVMRealPredicateFalse,
+            RealPredicate::OEQ => LLVMRealPredicate::LLVMRealOEQ,
+            RealPredicate::OGT => LLVMRealPredicate::LLVMRealOGT,
+            RealPredicate::OGE => LLVMRealPredicate::LLVMRealOGE,
+            RealPredicate::OLT => LLVMRealPredicate::LLVMRealOLT,
+            RealPredicate::OLE => LLVMRealPredicate::LLVMRealOLE,
+            RealPredicate::ONE => LLVMRealPredicate::LLVMRealONE,
+            RealPredicate::ORD => LLVMRealPredicate::LLVMRealORD,
+            RealPredicate::UNO => LLVMRealPredicate::LLVMRealUNO,
+            RealPredicate::UEQ => LLVMRealPredicate::LLVMRealUEQ,
+            RealPredicate::UGT => LLVMRealPredicate::LLVMRealUGT,
+            RealPredicate::UGE => LLVMRealPredicate::LLVMRealUGE,
+            RealPredicate::ULT => LLVMRealPredicate::LLVMRealULT,
+            RealPredicate::ULE => LLVMRealPredicate::LLVMRealULE,
+            RealPredicate::UNE => LLVMRealPredicate::LLVMRealUNE,
+            RealPredicate::True => LLVMRealPredicate::LLVMRealPredicateTrue
+        }
+    }
+}
@@ -0,0 +1,335 @@
+
+use llvm_sys::prelude::*;
+use llvm_sys::core::*;
+
+use std::slice;
+
+use super::context::Context;
+use super::value::{Value,raw_values};
+use super::types::Type;
+use super::builder::{IntPredicate,RealPredicate};
+
+macro_rules! const_unop {
+    ($name: ident, $op: ident) => {
+        pub fn $name(&self) -> Value<'ctx> {
+            unsafe {
+                Value::from_raw($op(self.inner()))
+            }
+        }
+    }
+}
+
+macro_rules! const_binop {
+    ($name: ident, $op: ident) => {
+        pub fn $name(&self, rhs: Value<'ctx>) -> Value<'ctx> {
+            unsafe {
+                Value::from_raw($op(self.inner(), rhs.inner()))
+            }
+        }
+    }
+}
+
+macro_rules! const_cast {
+    ($name: ident, $op: ident) => {
+        pub fn $name(&self, ty: Type<'ctx>) -> Value<'ctx> {
+            unsafe {
+                Value::from_raw($op(self.inner(), ty.inner()))
+            }
+        }
+    }
+}
+
+/// Constants of a given type
+impl<'ctx> Type<'ctx> {
+
+    /// Integer constant
+    ///
+    /// `sign_extend` controls how `val` is widened
+    /// if this type is wider then 64bits.
+    pub fn const_int(&self, val: u64, sign_extend: bool) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMConstInt(self.inner(), val, sign_extend as LLVMBool))
+        }
+    }
+
+    /// Integer constant of arbitrary precision
+    ///
+    /// `words` are little endian, least significant
+    /// word first.
+    pub fn const_int_arbitrary(&self, words: &[u64]) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMConstIntOfArbitraryPrecision(self.inner(), words.len() as u32, words.as_ptr()))
+        }
+    }
+
+    /// Floating point constant
+    pub fn const_real(&self, val: f64) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMConstReal(self.inner(), val))
+        }
+    }
+
+    /// The zero value of this type
+    pub fn const_null(&self) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMConstNull(self.inner()))
+        }
+    }
+
+    /// Every bit set, integer and vector types only
+    pub fn const_all_ones(&self) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMConstAllOnes(self.inner()))
+        }
+    }
+
+    /// The `undef` value of this type
+    pub fn const_undef(&self) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMGetUndef(self.inner()))
+        }
+    }
+
+    /// The null pointer of this (pointer) type
+    pub fn const_pointer_null(&self) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMConstPointerNull(self.inner()))
+        }
+    }
+
+    /// Constant array with elements of this type
+    pub fn const_array(&self, vals: &[Value<'ctx>]) -> Value<'ctx> {
+        let mut vals = raw_values(vals);
+        unsafe {
+            let len = vals.len() as u32;
+            Value::from_raw(LLVMConstArray(self.inner(), vals.as_mut_ptr(), len))
+        }
+    }
+
+    /// Constant of this named struct type
+    pub fn const_named_struct(&self, vals: &[Value<'ctx>]) -> Value<'ctx> {
+        let mut vals = raw_values(vals);
+        unsafe {
+            let len = vals.len() as u32;
+            Value::from_raw(LLVMConstNamedStruct(self.inner(), vals.as_mut_ptr(), len))
+        }
+    }
+
+    /// Size of this type as an `i64` constant expression
+    pub fn size_of(&self) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMSizeOf(self.inner()))
+        }
+    }
+
+    /// Alignment of this type as an `i64` constant expression
+    pub fn align_of(&self) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMAlignOf(self.inner()))
+        }
+    }
+}
+
+/// Constant construction and constant expressions
+impl<'ctx> Value<'ctx> {
+
+    /// Constant array of `i8` holding `bytes`
+    ///
+    /// `null_terminate` appends a trailing zero byte
+    pub fn const_string(ctx: &'ctx Context, bytes: &[u8], null_terminate: bool) -> Value<'ctx> {
+        unsafe {
+            let ptr = bytes.as_ptr() as *const _;
+            let dont = (!null_terminate) as LLVMBool;
+            Value::from_raw(LLVMConstStringInContext(ctx.inner(), ptr, bytes.len() as u32, dont))
+        }
+    }
+
+    /// Constant literal struct
+    pub fn const_struct(ctx: &'ctx Context, vals: &[Value<'ctx>], packed: bool) -> Value<'ctx> {
+        let mut vals = raw_values(vals);
+        unsafe {
+            let len = vals.len() as u32;
+            Value::from_raw(LLVMConstStructInContext(ctx.inner(), vals.as_mut_ptr(), len, packed as LLVMBool))
+        }
+    }
+
+    /// Constant SIMD vector
+    pub fn const_vector(vals: &[Value<'ctx>]) -> Value<'ctx> {
+        let mut vals = raw_values(vals);
+        unsafe {
+            let len = vals.len() as u32;
+            Value::from_raw(LLVMConstVector(vals.as_mut_ptr(), len))
+        }
+    }
+
+    /// Is this value a constant
+    pub fn is_constant(&self) -> bool {
+        unsafe {
+            LLVMIsConstant(self.inner()) != 0
+        }
+    }
+
+    /// Is this value `undef`
+    pub fn is_undef(&self) -> bool {
+        unsafe {
+            LLVMIsUndef(self.inner()) != 0
+        }
+    }
+
+    /// Is this the zero value of its type
+    pub fn is_null(&self) -> bool {
+        unsafe {
+            LLVMIsNull(self.inner()) != 0
+        }
+    }
+
+    /// Read back a constant integer zero extended
+    ///
+    /// Returns `None` if this isn't a constant integer
+    /// or doesn't fit in 64bits
+    pub fn as_const_int(&self) -> Option<u64> {
+        unsafe {
+            if LLVMIsAConstantInt(self.inner()).is_null() {
+                return None;
+            }
+            match self.get_type().int_width() {
+                Some(x) if x <= 64 => Some(LLVMConstIntGetZExtValue(self.inner())),
+                _ => None
+            }
+        }
+    }
+
+    /// Read back a constant integer sign extended
+    ///
+    /// Returns `None` if this isn't a constant integer
+    /// or doesn't fit in 64bits
+    pub fn as_const_sint(&self) -> Option<i64> {
+        unsafe {
+            if LLVMIsAConstantInt(self.inner()).is_null() {
+                return None;
+            }
+            match self.get_type().int_width() {
+                Some(x) if x <= 64 => Some(LLVMConstIntGetSExtValue(self.inner())),
+                _ => None
+            }
+        }
+    }
+
+    /// Read back a constant float as a double
+    ///
+    /// The bool is `true` if converting to a double
+    /// lost information.
+    pub fn as_const_real(&self) -> Option<(f64,bool)> {
+        unsafe {
+            if LLVMIsAConstantFP(self.inner()).is_null() {
+                return None;
+            }
+            let mut loses: LLVMBool = 0;
+            let val = LLVMConstRealGetDouble(self.inner(), &mut loses);
+            Some((val, loses != 0))
+        }
+    }
+
+    /// Read back the bytes of a constant string
+    ///
+    /// Returns `None` if this isn't an array of
+    /// `i8`. The bytes are owned by the Context.
+    pub fn as_const_string(&self) -> Option<&'ctx [u8]> {
+        unsafe {
+            if LLVMIsAConstantDataSequential(self.inner()).is_null() {
+                return None;
+            }
+            if LLVMIsConstantString(self.inner()) == 0 {
+                return None;
+            }
+            let mut len = 0usize;
+            let ptr = LLVMGetAsString(self.inner(), &mut len);
+            Some(slice::from_raw_parts(ptr as *const u8, len))
+        }
+    }
+
+    /// Constant Get Element Pointer
+    pub fn const_gep(&self, indices: &[Value<'ctx>]) -> Value<'ctx> {
+        let mut indices = raw_values(indices);
+        unsafe {
+            let len = indices.len() as u32;
+            Value::from_raw(LLVMConstGEP(self.inner(), indices.as_mut_ptr(), len))
+        }
+    }
+
+    /// Constant in bounds Get Element Pointer
+    pub fn const_inbounds_gep(&self, indices: &[Value<'ctx>]) -> Value<'ctx> {
+        let mut indices = raw_values(indices);
+        unsafe {
+            let len = indices.len() as u32;
+            Value::from_raw(LLVMConstInBoundsGEP(self.inner(), indices.as_mut_ptr(), len))
+        }
+    }
+
+    /// Constant integer comparison
+    pub fn const_icmp(&self, op: IntPredicate, rhs: Value<'ctx>) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMConstICmp(op.into(), self.inner(), rhs.inner()))
+        }
+    }
+
+    /// Constant floating point comparison
+    pub fn const_fcmp(&self, op: RealPredicate, rhs: Value<'ctx>) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMConstFCmp(op.into(), self.inner(), rhs.inner()))
+        }
+    }
+
+    /// Constant select between `then` and `other`
+    pub fn const_select(&self, then: Value<'ctx>, other: Value<'ctx>) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(LLVMConstSelect(self.inner(), then.inner(), other.inner()))
+        }
+    }
+
+    const_unop!(const_neg,LLVMConstNeg);
+    const_unop!(const_nsw_neg,LLVMConstNSWNeg);
+    const_unop!(const_nuw_neg,LLVMConstNUWNeg);
+    const_unop!(const_fneg,LLVMConstFNeg);
+    const_unop!(const_not,LLVMConstNot);
+    const_binop!(const_add,LLVMConstAdd);
+    const_binop!(const_nsw_add,LLVMConstNSWAdd);
+    const_binop!(const_nuw_add,LLVMConstNUWAdd);
+    const_binop!(const_fadd,LLVMConstFAdd);
+    const_binop!(const_sub,LLVMConstSub);
+    const_binop!(const_nsw_sub,LLVMConstNSWSub);
+    const_binop!(const_nuw_sub,LLVMConstNUWSub);
+    const_binop!(const_fsub,LLVMConstFSub);
+    const_binop!(const_mul,LLVMConstMul);
+    const_binop!(const_nsw_mul,LLVMConstNSWMul);
+    const_binop!(const_nuw_mul,LLVMConstNUWMul);
+    const_binop!(const_fmul,LLVMConstFMul);
+    const_binop!(const_udiv,LLVMConstUDiv);
+    const_binop!(const_sdiv,LLVMConstSDiv);
+    const_binop!(const_exact_sdiv,LLVMConstExactSDiv);
+    const_binop!(const_fdiv,LLVMConstFDiv);
+    const_binop!(const_urem,LLVMConstURem);
+    const_binop!(const_srem,LLVMConstSRem);
+    const_binop!(const_frem,LLVMConstFRem);
+    const_binop!(const_and,LLVMConstAnd);
+    const_binop!(const_or,LLVMConstOr);
+    const_binop!(const_xor,LLVMConstXor);
+    const_binop!(const_shl,LLVMConstShl);
+    const_binop!(const_lshr,LLVMConstLShr);
+    const_binop!(const_ashr,LLVMConstAShr);
+    const_cast!(const_trunc,LLVMConstTrunc);
+    const_cast!(const_sext,LLVMConstSExt);
+    const_cast!(const_zext,LLVMConstZExt);
+    const_cast!(const_fptrunc,LLVMConstFPTrunc);
+    const_cast!(const_fpext,LLVMConstFPExt);
+    const_cast!(const_uitofp,LLVMConstUIToFP);
+    const_cast!(const_sitofp,LLVMConstSIToFP);
+    const_cast!(const_fptoui,LLVMConstFPToUI);
+    const_cast!(const_fptosi,LLVMConstFPToSI);
+    const_cast!(const_ptrtoint,LLVMConstPtrToInt);
+    const_cast!(const_inttoptr,LLVMConstIntToPtr);
+    const_cast!(const_bitcast,LLVMConstBitCast);
+    const_cast!(const_addrspace_cast,LLVMConstAddrSpaceCast);
+    const_cast!(const_pointer_cast,LLVMConstPointerCast);
+    const_cast!(const_fp_cast,LLVMConstFPCast);
+}
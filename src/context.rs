@@ -0,0 +1,94 @@
+
+use llvm_sys::prelude::*;
+use llvm_sys::core::*;
+
+use std::default::Default;
+use std::marker::PhantomData;
+use std::cell::Cell;
+
+/// Abstraction around llvm::LLVMContext
+///
+/// A Context owns all the types, constants, and
+/// uniqued metadata the LLVM creates. Two different
+/// contexts share nothing, so each thread doing
+/// compilation should create its own.
+///
+/// Modules, Types, and Values borrow the Context
+/// that created them so the borrow checker won't
+/// let them outlive it. The borrow is invariant,
+/// so handles from the global context and from a
+/// local one can't be mixed:
+///
+/// ```compile_fail
+/// use llvm_bind::context::Context;
+/// use llvm_bind::module::Module;
+/// use llvm_bind::types::Type;
+///
+/// let local = Context::new();
+/// let mut module = Module::new("global").unwrap();
+/// let ty = Type::function(Type::void(&local), &[], false);
+/// module.add_function("f", ty).unwrap();
+/// ```
+///
+/// Two local contexts alive at the same time can
+/// still be mixed, the borrow checker sees no
+/// difference between their borrows.
+pub struct Context {
+    data: LLVMContextRef,
+    owned: bool
+}
+impl Drop for Context {
+    fn drop(&mut self) {
+        if self.owned {
+            unsafe {
+                LLVMContextDispose(self.data);
+            }
+        }
+    }
+}
+impl Context {
+
+    /// Create a new empty Context
+    pub fn new() -> Context {
+        unsafe {
+            Context {
+                data: LLVMContextCreate(),
+                owned: true
+            }
+        }
+    }
+
+    /// Handle to the LLVM's global context
+    ///
+    /// This is the context `Module::new` and `Buffer::parse_ir`
+    /// use. The global context is never disposed of, so
+    /// this borrow is `'static`. The global context is not
+    /// thread safe, so don't use it from more then one thread.
+    pub fn global() -> &'static Context {
+        thread_local! {
+            static GLOBAL: &'static Context = Box::leak(Box::new(Context {
+                data: unsafe { LLVMGetGlobalContext() },
+                owned: false
+            }));
+        }
+        GLOBAL.with(|ctx| *ctx)
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&self) -> LLVMContextRef {
+        self.data
+    }
+}
+impl Default for Context {
+    /// Returns a new owned context
+    fn default() -> Context {
+        Context::new()
+    }
+}
+
+/// Marks a handle as belonging to the Context `'ctx`
+///
+/// Invariant in `'ctx` so a handle can't be
+/// shortened to fit a different Context.
+pub(crate) type Brand<'ctx> = PhantomData<Cell<&'ctx Context>>;
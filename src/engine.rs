@@ -0,0 +1,409 @@
+
+use llvm_sys::prelude::*;
+use llvm_sys::core::*;
+use llvm_sys::execution_engine::*;
+
+use std::ffi::{CString,CStr};
+use std::os::raw::c_char;
+use std::os::raw::c_void;
+use std::marker::PhantomData;
+use std::ptr;
+use std::mem;
+
+use super::{Buffers,take_message};
+use super::context::Brand;
+use super::module::Module;
+use super::function::Function;
+use super::types::Type;
+use super::target::{CodeGenOptLevel,CodeModel,initialize_native};
+use super::error::{Error,Result};
+
+/// Abstraction around llvm::ExecutionEngine
+///
+/// Compiles modules to machine code, or interprets
+/// them, within this process so their functions
+/// can be called directly. The engine owns every
+/// module added to it.
+pub struct ExecutionEngine<'ctx> {
+    data: LLVMExecutionEngineRef,
+    modules: Vec<(LLVMModuleRef, Vec<Buffers>)>,
+    context: Brand<'ctx>
+}
+impl<'ctx> Drop for ExecutionEngine<'ctx> {
+    fn drop(&mut self) {
+        unsafe {
+            //disposes the modules it owns too
+            LLVMDisposeExecutionEngine(self.data);
+        }
+    }
+}
+impl<'ctx> ExecutionEngine<'ctx> {
+
+    /// Create an MCJIT engine with default options
+    pub fn new(module: Module<'ctx>) -> Result<ExecutionEngine<'ctx>> {
+        BuildExecutionEngine::new().build(module)
+    }
+
+    /// Create an engine that interprets the IR
+    ///
+    /// Slow, but needs no native code generation
+    /// and behaves the same on every machine.
+    pub fn interpreter(module: Module<'ctx>) -> Result<ExecutionEngine<'ctx>> {
+        unsafe {
+            LLVMLinkInInterpreter();
+            let (data, buffers) = module.split();
+            let mut ee: LLVMExecutionEngineRef = mem::zeroed();
+            let mut err: *mut c_char = mem::zeroed();
+            let flag = LLVMCreateInterpreterForModule(&mut ee, data, &mut err);
+            if flag != 0 {
+                //the LLVM frees the module on failure
+                return Err(Error::Engine(take_message(err)));
+            }
+            Ok(ExecutionEngine {
+                data: ee,
+                modules: vec![(data, buffers)],
+                context: PhantomData
+            })
+        }
+    }
+
+    /// Find the function `name` within any module
+    /// of this engine
+    ///
+    /// The function borrows the engine, as the
+    /// engine owns the module holding it.
+    pub fn find_function<'e, S: AsRef<str>>(&'e self, name: S) -> Option<Function<'e, 'ctx>> {
+        let name = match CString::new(name.as_ref()) {
+            Ok(x) => x,
+            Err(_) => return None
+        };
+        unsafe {
+            let mut func: LLVMValueRef = mem::zeroed();
+            if LLVMFindFunction(self.data, name.as_ptr(), &mut func) != 0 {
+                None
+            } else {
+                Some(Function::from_raw(func))
+            }
+        }
+    }
+
+    /// Call `func` with `args`
+    ///
+    /// Nothing checks that `args` match the
+    /// function's parameters, and native code
+    /// the MCJIT produced is run as is.
+    pub unsafe fn run_function(&self, func: &Function<'_, 'ctx>, args: &[GenericValue]) -> GenericValue {
+        let mut args: Vec<LLVMGenericValueRef> = args.iter().map(|a| a.data).collect();
+        let len = args.len() as u32;
+        GenericValue::from_raw(LLVMRunFunction(self.data, func.inner(), len, args.as_mut_ptr()))
+    }
+
+    /// Call `func` as if it were `main`
+    ///
+    /// `args` become `argv` and `env` becomes
+    /// `envp`. Returns what `func` returned.
+    pub unsafe fn run_function_as_main<A: AsRef<str>, E: AsRef<str>>(&self, func: &Function<'_, 'ctx>, args: &[A], env: &[E]) -> Result<i32> {
+        let args = args.iter()
+            .map(|x| CString::new(x.as_ref()))
+            .collect::<::std::result::Result<Vec<CString>,_>>()?;
+        let env = env.iter()
+            .map(|x| CString::new(x.as_ref()))
+            .collect::<::std::result::Result<Vec<CString>,_>>()?;
+        let argv: Vec<*const c_char> = args.iter().map(|x| x.as_ptr()).collect();
+        let mut envp: Vec<*const c_char> = env.iter().map(|x| x.as_ptr()).collect();
+        envp.push(ptr::null());
+        Ok(LLVMRunFunctionAsMain(self.data, func.inner(), argv.len() as u32, argv.as_ptr(), envp.as_ptr()))
+    }
+
+    /// Address of the compiled function `name`
+    ///
+    /// Compiles the module holding it if that
+    /// hasn't happened yet. Returns `None` if no
+    /// function has that name.
+    pub fn get_function_address<S: AsRef<str>>(&self, name: S) -> Option<u64> {
+        let name = match CString::new(name.as_ref()) {
+            Ok(x) => x,
+            Err(_) => return None
+        };
+        let addr = unsafe {
+            LLVMGetFunctionAddress(self.data, name.as_ptr())
+        };
+        if addr == 0 {
+            None
+        } else {
+            Some(addr)
+        }
+    }
+
+    /// Address of the global variable `name`
+    ///
+    /// Returns `None` if no global has that name.
+    pub fn get_global_address<S: AsRef<str>>(&self, name: S) -> Option<u64> {
+        let name = match CString::new(name.as_ref()) {
+            Ok(x) => x,
+            Err(_) => return None
+        };
+        let addr = unsafe {
+            LLVMGetGlobalValueAddress(self.data, name.as_ptr())
+        };
+        if addr == 0 {
+            None
+        } else {
+            Some(addr)
+        }
+    }
+
+    /// The compiled function `name` as a Rust function
+    ///
+    /// `F` should be an `extern "C" fn` type
+    /// matching the function's signature, nothing
+    /// checks that it does. The function is only
+    /// valid while this engine is alive.
+    ///
+    /// #Panic:
+    ///
+    /// This function will panic if `F` isn't
+    /// pointer sized.
+    pub unsafe fn get_function<F: Copy, S: AsRef<str>>(&self, name: S) -> Option<F> {
+        assert_eq!(mem::size_of::<F>(), mem::size_of::<usize>(), "F must be a function pointer");
+        self.get_function_address(name)
+            .map(|addr| mem::transmute_copy(&(addr as usize)))
+    }
+
+    /// Add another module to this engine
+    pub fn add_module(&mut self, module: Module<'ctx>) {
+        unsafe {
+            let (data, buffers) = module.split();
+            LLVMAddModule(self.data, data);
+            self.modules.push((data, buffers));
+        }
+    }
+
+    /// Remove a module from this engine
+    ///
+    /// Looks the module up by name and hands
+    /// ownership of it back.
+    pub fn remove_module<S: AsRef<str>>(&mut self, name: S) -> Result<Module<'ctx>> {
+        let idx = self.modules.iter()
+            .position(|&(m, _)| unsafe { module_name(m) } == name.as_ref().as_bytes());
+        let idx = match idx {
+            Some(x) => x,
+            None => return Err(Error::Engine(format!("no module named `{}`", name.as_ref())))
+        };
+        unsafe {
+            let mut out: LLVMModuleRef = mem::zeroed();
+            let mut err: *mut c_char = mem::zeroed();
+            let flag = LLVMRemoveModule(self.data, self.modules[idx].0, &mut out, &mut err);
+            if flag != 0 {
+                return Err(Error::Engine(take_message(err)));
+            }
+            let (_, buffers) = self.modules.remove(idx);
+            Ok(Module::from_raw(out, buffers))
+        }
+    }
+
+    /// Run the static constructors of every module
+    pub fn run_static_constructors(&mut self) {
+        unsafe {
+            LLVMRunStaticConstructors(self.data);
+        }
+    }
+
+    /// Run the static destructors of every module
+    pub fn run_static_destructors(&mut self) {
+        unsafe {
+            LLVMRunStaticDestructors(self.data);
+        }
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&self) -> LLVMExecutionEngineRef {
+        self.data
+    }
+}
+
+/// Abstraction around llvm::GenericValue
+///
+/// Arguments to, and results from, functions
+/// run with `ExecutionEngine::run_function`.
+pub struct GenericValue {
+    data: LLVMGenericValueRef
+}
+impl Drop for GenericValue {
+    fn drop(&mut self) {
+        unsafe {
+            LLVMDisposeGenericValue(self.data);
+        }
+    }
+}
+impl GenericValue {
+
+    /// Integer of the integer type `ty`
+    ///
+    /// `signed` controls how `val` is extended
+    /// if `ty` is wider then 64bits.
+    pub fn from_int(ty: Type, val: u64, signed: bool) -> GenericValue {
+        unsafe {
+            GenericValue::from_raw(LLVMCreateGenericValueOfInt(ty.inner(), val, signed as LLVMBool))
+        }
+    }
+
+    /// Float of the floating point type `ty`
+    ///
+    /// Only `float` and `double` are supported.
+    pub fn from_float(ty: Type, val: f64) -> GenericValue {
+        unsafe {
+            GenericValue::from_raw(LLVMCreateGenericValueOfFloat(ty.inner(), val))
+        }
+    }
+
+    /// Pointer
+    pub fn from_pointer<T>(ptr: *mut T) -> GenericValue {
+        unsafe {
+            GenericValue::from_raw(LLVMCreateGenericValueOfPointer(ptr as *mut c_void))
+        }
+    }
+
+    /// Width in bits if this holds an integer
+    pub fn int_width(&self) -> u32 {
+        unsafe {
+            LLVMGenericValueIntWidth(self.data)
+        }
+    }
+
+    /// Read back an integer
+    ///
+    /// `signed` sign extends values narrower then
+    /// 64bits, otherwise they're zero extended.
+    pub fn as_int(&self, signed: bool) -> u64 {
+        unsafe {
+            LLVMGenericValueToInt(self.data, signed as LLVMBool)
+        }
+    }
+
+    /// Read back a float of type `ty`
+    pub fn as_float(&self, ty: Type) -> f64 {
+        unsafe {
+            LLVMGenericValueToFloat(ty.inner(), self.data)
+        }
+    }
+
+    /// Read back a pointer
+    pub fn as_pointer<T>(&self) -> *mut T {
+        unsafe {
+            LLVMGenericValueToPointer(self.data) as *mut T
+        }
+    }
+
+    /// Method internal to the library.
+    pub unsafe fn from_raw(data: LLVMGenericValueRef) -> GenericValue {
+        GenericValue {
+            data: data
+        }
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&self) -> LLVMGenericValueRef {
+        self.data
+    }
+}
+
+/// Identifier of a raw module
+unsafe fn module_name<'a>(module: LLVMModuleRef) -> &'a [u8] {
+    let mut len = 0usize;
+    let ptr = LLVMGetModuleIdentifier(module, &mut len);
+    CStr::from_ptr(ptr).to_bytes()
+}
+
+/// Execution Engine Builder
+///
+/// Sets the MCJIT options before handing a
+/// `Module` over to create the engine.
+pub struct BuildExecutionEngine {
+    opt_level: CodeGenOptLevel,
+    code_model: CodeModel,
+    no_frame_pointer_elim: bool,
+    fast_isel: bool
+}
+impl BuildExecutionEngine {
+
+    /// This sets the default options.
+    ///
+    /// Same as `ExecutionEngine::new` if built
+    /// right away.
+    pub fn new() -> BuildExecutionEngine {
+        BuildExecutionEngine {
+            opt_level: CodeGenOptLevel::None,
+            code_model: CodeModel::JIT,
+            no_frame_pointer_elim: false,
+            fast_isel: false
+        }
+    }
+
+    pub fn set_opt_level(&mut self, opt: CodeGenOptLevel) -> &mut Self {
+        self.opt_level = opt;
+        self
+    }
+
+    pub fn set_code_model(&mut self, model: CodeModel) -> &mut Self {
+        self.code_model = model;
+        self
+    }
+
+    /// Keep frame pointers, useful for debuggers
+    /// and profilers
+    pub fn set_no_frame_pointer_elim(&mut self, flag: bool) -> &mut Self {
+        self.no_frame_pointer_elim = flag;
+        self
+    }
+
+    /// Use the faster but worse instruction selector
+    pub fn set_fast_isel(&mut self, flag: bool) -> &mut Self {
+        self.fast_isel = flag;
+        self
+    }
+
+    /// Complete the `ExecutionEngine` builder pattern
+    ///
+    /// Initializes the native target if that hasn't
+    /// been done yet.
+    pub fn build<'ctx>(self, module: Module<'ctx>) -> Result<ExecutionEngine<'ctx>> {
+        initialize_native()?;
+        unsafe {
+            LLVMLinkInMCJIT();
+            let size = mem::size_of::<LLVMMCJITCompilerOptions>();
+            let mut opts: LLVMMCJITCompilerOptions = mem::zeroed();
+            LLVMInitializeMCJITCompilerOptions(&mut opts, size);
+            opts.OptLevel = match self.opt_level {
+                CodeGenOptLevel::None => 0,
+                CodeGenOptLevel::Less => 1,
+                CodeGenOptLevel::Default => 2,
+                CodeGenOptLevel::Aggressive => 3
+            };
+            opts.CodeModel = self.code_model.into();
+            opts.NoFramePointerElim = self.no_frame_pointer_elim as LLVMBool;
+            opts.EnableFastISel = self.fast_isel as LLVMBool;
+
+            let (data, buffers) = module.split();
+            let mut ee: LLVMExecutionEngineRef = mem::zeroed();
+            let mut err: *mut c_char = mem::zeroed();
+            let flag = LLVMCreateMCJITCompilerForModule(&mut ee, data, &mut opts, size, &mut err);
+            if flag != 0 {
+                //the LLVM frees the module on failure
+                return Err(Error::Engine(take_message(err)));
+            }
+            Ok(ExecutionEngine {
+                data: ee,
+                modules: vec![(data, buffers)],
+                context: PhantomData
+            })
+        }
+    }
+}
+impl Default for BuildExecutionEngine {
+    fn default() -> BuildExecutionEngine {
+        BuildExecutionEngine::new()
+    }
+}
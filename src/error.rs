@@ -0,0 +1,110 @@
+
+use std::ffi::NulError;
+use std::fmt;
+use std::error;
+use std::io;
+use std::result;
+
+use super::buffer::ParseError;
+use super::target::{TargetError,CodeGenError};
+use super::pipeline::PipelineError;
+
+/// Result type used throughout the library
+pub type Result<T> = result::Result<T,Error>;
+
+/// Every way a call into this library can fail
+#[derive(Debug)]
+pub enum Error {
+    /// A Module or Function failed verification
+    Verify(String),
+    /// IR or bitcode could not be parsed
+    Parse(ParseError),
+    /// A Target could not be found
+    Target(TargetError),
+    /// Code generation failed
+    CodeGen(CodeGenError),
+    /// A pass pipeline description was malformed
+    Pipeline(PipelineError),
+    /// An execution engine couldn't be created or used
+    Engine(String),
+    /// Modules couldn't be linked together
+    Link(String),
+    /// Reading or writing a file failed
+    Io(io::Error),
+    /// A string handed to the LLVM contained a null byte
+    InvalidString(NulError)
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Verify(ref msg) => write!(f, "verification failed: {}", msg),
+            Error::Parse(ref err) => write!(f, "parse error: {}", err),
+            Error::Target(ref err) => write!(f, "target error: {}", err),
+            Error::CodeGen(ref err) => write!(f, "code generation error: {}", err),
+            Error::Pipeline(ref err) => write!(f, "pipeline error: {}", err),
+            Error::Engine(ref msg) => write!(f, "execution engine error: {}", msg),
+            Error::Link(ref msg) => write!(f, "link error: {}", msg),
+            Error::Io(ref err) => write!(f, "io error: {}", err),
+            Error::InvalidString(ref err) => write!(f, "invalid string: {}", err)
+        }
+    }
+}
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Verify(_) => "verification failed",
+            Error::Parse(_) => "parse error",
+            Error::Target(_) => "target error",
+            Error::CodeGen(_) => "code generation error",
+            Error::Pipeline(_) => "pipeline error",
+            Error::Engine(_) => "execution engine error",
+            Error::Link(_) => "link error",
+            Error::Io(_) => "io error",
+            Error::InvalidString(_) => "invalid string"
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Verify(_) => None,
+            Error::Parse(ref err) => Some(err),
+            Error::Target(ref err) => Some(err),
+            Error::CodeGen(ref err) => Some(err),
+            Error::Pipeline(ref err) => Some(err),
+            Error::Engine(_) => None,
+            Error::Link(_) => None,
+            Error::Io(ref err) => Some(err),
+            Error::InvalidString(ref err) => Some(err)
+        }
+    }
+}
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Error {
+        Error::Parse(err)
+    }
+}
+impl From<TargetError> for Error {
+    fn from(err: TargetError) -> Error {
+        Error::Target(err)
+    }
+}
+impl From<CodeGenError> for Error {
+    fn from(err: CodeGenError) -> Error {
+        Error::CodeGen(err)
+    }
+}
+impl From<PipelineError> for Error {
+    fn from(err: PipelineError) -> Error {
+        Error::Pipeline(err)
+    }
+}
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+impl From<NulError> for Error {
+    fn from(err: NulError) -> Error {
+        Error::InvalidString(err)
+    }
+}
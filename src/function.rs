@@ -0,0 +1,305 @@
+
+use llvm_sys::prelude::*;
+use llvm_sys::core::*;
+use llvm_sys::analysis::*;
+
+use std::ffi::{CString,CStr};
+use std::marker::PhantomData;
+use std::fmt;
+
+use super::value::{Value,Linkage};
+use super::types::Type;
+use super::block::{BasicBlock,BasicBlocks};
+use super::module::Module;
+use super::take_message;
+use super::error::{Error,Result};
+
+/// Abstraction around llvm::Function
+///
+/// Functions are owned by the `Module` they
+/// were added to. This is just a handle to one,
+/// which borrows the module so it can't outlive
+/// it:
+///
+/// ```compile_fail
+/// use llvm_bind::context::Context;
+/// use llvm_bind::module::Module;
+/// use llvm_bind::types::Type;
+///
+/// let ctx = Context::new();
+/// let func = {
+///     let module = Module::new_in(&ctx, "m").unwrap();
+///     module.add_function("f", Type::function(Type::void(&ctx), &[], false)).unwrap()
+/// };
+/// func.verify().unwrap();
+/// ```
+#[derive(Copy,Clone)]
+pub struct Function<'m, 'ctx: 'm> {
+    data: LLVMValueRef,
+    module: PhantomData<&'m Module<'ctx>>
+}
+impl<'m, 'ctx> Function<'m, 'ctx> {
+
+    /// Get the name of this item
+    pub fn get_name(&self) -> CString {
+        unsafe {
+            CStr::from_ptr(LLVMGetValueName(self.data)).to_owned()
+        }
+    }
+
+    /// The function's type
+    ///
+    /// This is the function type itself, not the
+    /// pointer to it `get_type` on the value returns.
+    pub fn function_type(&self) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMGetElementType(LLVMTypeOf(self.data)))
+        }
+    }
+
+    /// Number of parameters this function takes
+    pub fn count_params(&self) -> u32 {
+        unsafe {
+            LLVMCountParams(self.data)
+        }
+    }
+
+    /// Get parameter `index`
+    pub fn param(&self, index: u32) -> Option<Value<'ctx>> {
+        if index >= self.count_params() {
+            return None;
+        }
+        unsafe {
+            Some(Value::from_raw(LLVMGetParam(self.data, index)))
+        }
+    }
+
+    /// All the parameters of this function
+    pub fn params(&self) -> Vec<Value<'ctx>> {
+        (0..self.count_params())
+            .map(|i| unsafe { Value::from_raw(LLVMGetParam(self.data, i)) })
+            .collect()
+    }
+
+    /// Get the calling convention
+    pub fn call_conv(&self) -> CallConv {
+        unsafe {
+            LLVMGetFunctionCallConv(self.data).into()
+        }
+    }
+
+    /// Set the calling convention
+    pub fn set_call_conv(&self, cc: CallConv) {
+        unsafe {
+            LLVMSetFunctionCallConv(self.data, cc.into());
+        }
+    }
+
+    /// Get the linkage
+    pub fn linkage(&self) -> Linkage {
+        unsafe {
+            LLVMGetLinkage(self.data).into()
+        }
+    }
+
+    /// Set the linkage
+    pub fn set_linkage(&self, linkage: Linkage) {
+        unsafe {
+            LLVMSetLinkage(self.data, linkage.into());
+        }
+    }
+
+    /// Is this only a declaration (no body)
+    pub fn is_declaration(&self) -> bool {
+        unsafe {
+            LLVMIsDeclaration(self.data) != 0
+        }
+    }
+
+    /// Append a new empty basic block to the end
+    /// of this function
+    pub fn append_basic_block<S: AsRef<str>>(&self, name: S) -> Result<BasicBlock<'m, 'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            let ctx = LLVMGetTypeContext(LLVMTypeOf(self.data));
+            Ok(BasicBlock::from_raw(LLVMAppendBasicBlockInContext(ctx, self.data, name.as_ptr())))
+        }
+    }
+
+    /// The block execution starts in
+    ///
+    /// Returns `None` for declarations
+    pub fn entry_block(&self) -> Option<BasicBlock<'m, 'ctx>> {
+        if self.is_declaration() {
+            return None;
+        }
+        unsafe {
+            Some(BasicBlock::from_raw(LLVMGetEntryBasicBlock(self.data)))
+        }
+    }
+
+    /// Number of basic blocks in this function
+    pub fn count_basic_blocks(&self) -> u32 {
+        unsafe {
+            LLVMCountBasicBlocks(self.data)
+        }
+    }
+
+    /// Iterate over the basic blocks in this function
+    pub fn basic_blocks(&self) -> BasicBlocks<'m, 'ctx> {
+        unsafe {
+            BasicBlocks::from_raw(LLVMGetFirstBasicBlock(self.data))
+        }
+    }
+
+    /// Verify Function Contents
+    ///
+    /// The LLVM-C API only reports pass or fail
+    /// for a single function, verify the `Module`
+    /// for a full description.
+    pub fn verify(&self) -> Result<()> {
+        let flag = unsafe {
+            LLVMVerifyFunction(self.data, LLVMVerifierFailureAction::LLVMReturnStatusAction)
+        };
+        if flag != 0 {
+            let name = self.get_name().to_string_lossy().into_owned();
+            Err(Error::Verify(format!("function `{}` is malformed", name)))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Remove this function from its module and free it
+    ///
+    /// #Safety:
+    ///
+    /// `Function` is `Copy`, so other handles to it
+    /// may still exist. None of them, nor its
+    /// parameters, blocks, or instructions may be
+    /// used afterwards, and nothing may still call
+    /// or refer to it.
+    pub unsafe fn delete(self) {
+        LLVMDeleteFunction(self.data);
+    }
+
+    /// The function as a plain value
+    ///
+    /// Used for passing it to `Builder::build_call`
+    pub fn as_value(&self) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(self.data)
+        }
+    }
+
+    /// Method internal to the library.
+    ///
+    /// The caller picks `'m`, it must not outlive
+    /// the module holding the function.
+    pub unsafe fn from_raw(data: LLVMValueRef) -> Function<'m, 'ctx> {
+        Function {
+            data: data,
+            module: PhantomData
+        }
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&self) -> LLVMValueRef {
+        self.data
+    }
+}
+impl<'m, 'ctx> fmt::Display for Function<'m, 'ctx> {
+    /// Prints the textual IR
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = unsafe {
+            take_message(LLVMPrintValueToString(self.data))
+        };
+        f.write_str(&s)
+    }
+}
+impl<'m, 'ctx> fmt::Debug for Function<'m, 'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Iterator over the functions in a `Module`
+///
+/// Returned by `Module::functions`
+pub struct Functions<'m, 'ctx: 'm> {
+    next: LLVMValueRef,
+    module: PhantomData<&'m Module<'ctx>>
+}
+impl<'m, 'ctx> Functions<'m, 'ctx> {
+
+    /// Method internal to the library.
+    pub unsafe fn from_raw(first: LLVMValueRef) -> Functions<'m, 'ctx> {
+        Functions {
+            next: first,
+            module: PhantomData
+        }
+    }
+}
+impl<'m, 'ctx> Iterator for Functions<'m, 'ctx> {
+    type Item = Function<'m, 'ctx>;
+    fn next(&mut self) -> Option<Function<'m, 'ctx>> {
+        if self.next.is_null() {
+            return None;
+        }
+        unsafe {
+            let f = Function::from_raw(self.next);
+            self.next = LLVMGetNextFunction(self.next);
+            Some(f)
+        }
+    }
+}
+
+/// Calling conventions
+///
+/// `Other` covers target specific conventions
+/// the LLVM-C API doesn't name.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum CallConv {
+    C,
+    Fast,
+    Cold,
+    WebKitJS,
+    AnyReg,
+    X86Stdcall,
+    X86Fastcall,
+    Other(u32)
+}
+impl Into<u32> for CallConv {
+    fn into(self) -> u32 {
+        match self {
+            CallConv::C => 0,
+            CallConv::Fast => 8,
+            CallConv::Cold => 9,
+            CallConv::WebKitJS => 12,
+            CallConv::AnyReg => 13,
+            CallConv::X86Stdcall => 64,
+            CallConv::X86Fastcall => 65,
+            CallConv::Other(cc) => cc
+        }
+    }
+}
+impl From<u32> for CallConv {
+    fn from(cc: u32) -> CallConv {
+        match cc {
+            0 => CallConv::C,
+            8 => CallConv::Fast,
+            9 => CallConv::Cold,
+            12 => CallConv::WebKitJS,
+            13 => CallConv::AnyReg,
+            64 => CallConv::X86Stdcall,
+            65 => CallConv::X86Fastcall,
+            x => CallConv::Other(x)
+        }
+    }
+}
+impl Default for CallConv {
+    /// The C calling convention is the LLVM's default
+    fn default() -> CallConv {
+        CallConv::C
+    }
+}
@@ -0,0 +1,367 @@
+
+use llvm_sys::*;
+use llvm_sys::prelude::*;
+use llvm_sys::core::*;
+
+use std::ffi::{CString,CStr};
+use std::marker::PhantomData;
+use std::fmt;
+
+use super::value::{Value,Linkage};
+use super::module::Module;
+use super::take_message;
+use super::error::Result;
+
+/// Abstraction around llvm::GlobalVariable
+///
+/// Global variables are owned by the `Module`
+/// they were added to. This is just a handle
+/// to one, which borrows the module so it
+/// can't outlive it.
+#[derive(Copy,Clone)]
+pub struct GlobalVariable<'m, 'ctx: 'm> {
+    data: LLVMValueRef,
+    module: PhantomData<&'m Module<'ctx>>
+}
+impl<'m, 'ctx> GlobalVariable<'m, 'ctx> {
+
+    /// Get the name of this item
+    pub fn get_name(&self) -> CString {
+        unsafe {
+            CStr::from_ptr(LLVMGetValueName(self.data)).to_owned()
+        }
+    }
+
+    /// Get the initial value
+    ///
+    /// Returns `None` for external declarations
+    pub fn initializer(&self) -> Option<Value<'ctx>> {
+        unsafe {
+            let ptr = LLVMGetInitializer(self.data);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(Value::from_raw(ptr))
+            }
+        }
+    }
+
+    /// Set the initial value
+    ///
+    /// `val` must be a constant of the global's type
+    pub fn set_initializer(&self, val: Value<'ctx>) {
+        unsafe {
+            LLVMSetInitializer(self.data, val.inner());
+        }
+    }
+
+    /// Is this global immutable
+    pub fn is_constant(&self) -> bool {
+        unsafe {
+            LLVMIsGlobalConstant(self.data) != 0
+        }
+    }
+
+    /// Mark this global immutable
+    pub fn set_constant(&self, flag: bool) {
+        unsafe {
+            LLVMSetGlobalConstant(self.data, flag as LLVMBool);
+        }
+    }
+
+    /// Get the linkage
+    pub fn linkage(&self) -> Linkage {
+        unsafe {
+            LLVMGetLinkage(self.data).into()
+        }
+    }
+
+    /// Set the linkage
+    pub fn set_linkage(&self, linkage: Linkage) {
+        unsafe {
+            LLVMSetLinkage(self.data, linkage.into());
+        }
+    }
+
+    /// Get the visibility
+    pub fn visibility(&self) -> Visibility {
+        unsafe {
+            LLVMGetVisibility(self.data).into()
+        }
+    }
+
+    /// Set the visibility
+    pub fn set_visibility(&self, vis: Visibility) {
+        unsafe {
+            LLVMSetVisibility(self.data, vis.into());
+        }
+    }
+
+    /// Get the DLL storage class
+    pub fn dll_storage_class(&self) -> DLLStorageClass {
+        unsafe {
+            LLVMGetDLLStorageClass(self.data).into()
+        }
+    }
+
+    /// Set the DLL storage class
+    pub fn set_dll_storage_class(&self, class: DLLStorageClass) {
+        unsafe {
+            LLVMSetDLLStorageClass(self.data, class.into());
+        }
+    }
+
+    /// Get the thread local mode
+    pub fn thread_local_mode(&self) -> ThreadLocalMode {
+        unsafe {
+            LLVMGetThreadLocalMode(self.data).into()
+        }
+    }
+
+    /// Set the thread local mode
+    ///
+    /// `ThreadLocalMode::NotThreadLocal` makes this
+    /// a normal global again.
+    pub fn set_thread_local_mode(&self, mode: ThreadLocalMode) {
+        unsafe {
+            LLVMSetThreadLocalMode(self.data, mode.into());
+        }
+    }
+
+    /// Get the alignment in bytes
+    ///
+    /// Zero means the target's default
+    pub fn alignment(&self) -> u32 {
+        unsafe {
+            LLVMGetAlignment(self.data)
+        }
+    }
+
+    /// Set the alignment in bytes
+    pub fn set_alignment(&self, bytes: u32) {
+        unsafe {
+            LLVMSetAlignment(self.data, bytes);
+        }
+    }
+
+    /// Get the section this global is placed in
+    ///
+    /// Empty if no section was set. The name is
+    /// copied as `set_section` frees it.
+    pub fn section(&self) -> CString {
+        unsafe {
+            CStr::from_ptr(LLVMGetSection(self.data)).to_owned()
+        }
+    }
+
+    /// Set the section this global is placed in
+    pub fn set_section<S: AsRef<str>>(&self, section: S) -> Result<()> {
+        let section = CString::new(section.as_ref())?;
+        unsafe {
+            LLVMSetSection(self.data, section.as_ptr());
+        }
+        Ok(())
+    }
+
+    /// Is the address of this global insignificant
+    pub fn has_unnamed_addr(&self) -> bool {
+        unsafe {
+            LLVMHasUnnamedAddr(self.data) != 0
+        }
+    }
+
+    /// Mark the address of this global as insignificant
+    ///
+    /// This lets the LLVM merge it with identical
+    /// constants.
+    pub fn set_unnamed_addr(&self, flag: bool) {
+        unsafe {
+            LLVMSetUnnamedAddr(self.data, flag as LLVMBool);
+        }
+    }
+
+    /// Remove this global from its module and free it
+    ///
+    /// #Safety:
+    ///
+    /// `GlobalVariable` is `Copy`, so other handles
+    /// to it may still exist. None of them may be
+    /// used afterwards, and no instruction or
+    /// constant may still refer to it.
+    pub unsafe fn delete(self) {
+        LLVMDeleteGlobal(self.data);
+    }
+
+    /// The global as a plain value
+    ///
+    /// This is a pointer to the global's storage
+    pub fn as_value(&self) -> Value<'ctx> {
+        unsafe {
+            Value::from_raw(self.data)
+        }
+    }
+
+    /// Method internal to the library.
+    ///
+    /// The caller picks `'m`, it must not outlive
+    /// the module holding the global.
+    pub unsafe fn from_raw(data: LLVMValueRef) -> GlobalVariable<'m, 'ctx> {
+        GlobalVariable {
+            data: data,
+            module: PhantomData
+        }
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&self) -> LLVMValueRef {
+        self.data
+    }
+}
+impl<'m, 'ctx> fmt::Display for GlobalVariable<'m, 'ctx> {
+    /// Prints the textual IR
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = unsafe {
+            take_message(LLVMPrintValueToString(self.data))
+        };
+        f.write_str(&s)
+    }
+}
+impl<'m, 'ctx> fmt::Debug for GlobalVariable<'m, 'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Iterator over the global variables in a `Module`
+///
+/// Returned by `Module::globals`
+pub struct Globals<'m, 'ctx: 'm> {
+    next: LLVMValueRef,
+    module: PhantomData<&'m Module<'ctx>>
+}
+impl<'m, 'ctx> Globals<'m, 'ctx> {
+
+    /// Method internal to the library.
+    pub unsafe fn from_raw(first: LLVMValueRef) -> Globals<'m, 'ctx> {
+        Globals {
+            next: first,
+            module: PhantomData
+        }
+    }
+}
+impl<'m, 'ctx> Iterator for Globals<'m, 'ctx> {
+    type Item = GlobalVariable<'m, 'ctx>;
+    fn next(&mut self) -> Option<GlobalVariable<'m, 'ctx>> {
+        if self.next.is_null() {
+            return None;
+        }
+        unsafe {
+            let g = GlobalVariable::from_raw(self.next);
+            self.next = LLVMGetNextGlobal(self.next);
+            Some(g)
+        }
+    }
+}
+
+/// Symbol visibility
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum Visibility {
+    Default,
+    Hidden,
+    Protected
+}
+impl Into<LLVMVisibility> for Visibility {
+    fn into(self) -> LLVMVisibility {
+        match self {
+            Visibility::Default => LLVMVisibility::LLVMDefaultVisibility,
+            Visibility::Hidden => LLVMVisibility::LLVMHiddenVisibility,
+            Visibility::Protected => LLVMVisibility::LLVMProtectedVisibility
+        }
+    }
+}
+impl From<LLVMVisibility> for Visibility {
+    fn from(vis: LLVMVisibility) -> Visibility {
+        match vis {
+            LLVMVisibility::LLVMDefaultVisibility => Visibility::Default,
+            LLVMVisibility::LLVMHiddenVisibility => Visibility::Hidden,
+            LLVMVisibility::LLVMProtectedVisibility => Visibility::Protected
+        }
+    }
+}
+impl Default for Visibility {
+    fn default() -> Visibility {
+        Visibility::Default
+    }
+}
+
+/// Windows DLL storage class
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum DLLStorageClass {
+    Default,
+    DLLImport,
+    DLLExport
+}
+impl Into<LLVMDLLStorageClass> for DLLStorageClass {
+    fn into(self) -> LLVMDLLStorageClass {
+        match self {
+            DLLStorageClass::Default => LLVMDLLStorageClass::LLVMDefaultStorageClass,
+            DLLStorageClass::DLLImport => LLVMDLLStorageClass::LLVMDLLImportStorageClass,
+            DLLStorageClass::DLLExport => LLVMDLLStorageClass::LLVMDLLExportStorageClass
+        }
+    }
+}
+impl From<LLVMDLLStorageClass> for DLLStorageClass {
+    fn from(class: LLVMDLLStorageClass) -> DLLStorageClass {
+        match class {
+            LLVMDLLStorageClass::LLVMDefaultStorageClass => DLLStorageClass::Default,
+            LLVMDLLStorageClass::LLVMDLLImportStorageClass => DLLStorageClass::DLLImport,
+            LLVMDLLStorageClass::LLVMDLLExportStorageClass => DLLStorageClass::DLLExport
+        }
+    }
+}
+impl Default for DLLStorageClass {
+    fn default() -> DLLStorageClass {
+        DLLStorageClass::Default
+    }
+}
+
+/// Thread local storage model
+///
+/// The models run from most general (and slowest)
+/// to most restrictive (and fastest).
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum ThreadLocalMode {
+    NotThreadLocal,
+    GeneralDynamic,
+    LocalDynamic,
+    InitialExec,
+    LocalExec
+}
+impl Into<LLVMThreadLocalMode> for ThreadLocalMode {
+    fn into(self) -> LLVMThreadLocalMode {
+        match self {
+            ThreadLocalMode::NotThreadLocal => LLVMThreadLocalMode::LLVMNotThreadLocal,
+            ThreadLocalMode::GeneralDynamic => LLVMThreadLocalMode::LLVMGeneralDynamicTLSModel,
+            ThreadLocalMode::LocalDynamic => LLVMThreadLocalMode::LLVMLocalDynamicTLSModel,
+            ThreadLocalMode::InitialExec => LLVMThreadLocalMode::LLVMInitialExecTLSModel,
+            ThreadLocalMode::LocalExec => LLVMThreadLocalMode::LLVMLocalExecTLSModel
+        }
+    }
+}
+impl From<LLVMThreadLocalMode> for ThreadLocalMode {
+    fn from(mode: LLVMThreadLocalMode) -> ThreadLocalMode {
+        match mode {
+            LLVMThreadLocalMode::LLVMNotThreadLocal => ThreadLocalMode::NotThreadLocal,
+            LLVMThreadLocalMode::LLVMGeneralDynamicTLSModel => ThreadLocalMode::GeneralDynamic,
+            LLVMThreadLocalMode::LLVMLocalDynamicTLSModel => ThreadLocalMode::LocalDynamic,
+            LLVMThreadLocalMode::LLVMInitialExecTLSModel => ThreadLocalMode::InitialExec,
+            LLVMThreadLocalMode::LLVMLocalExecTLSModel => ThreadLocalMode::LocalExec
+        }
+    }
+}
+impl Default for ThreadLocalMode {
+    fn default() -> ThreadLocalMode {
+        ThreadLocalMode::NotThreadLocal
+    }
+}
@@ -21,9 +21,64 @@ use llvm_sys::target_machine::*;
 
 use std::ffi::{CString,CStr};
 use std::os::raw::c_char;
+use std::path::Path;
+use std::io;
 
 
 
+/// Errors
+///
+/// The single error type every fallible
+/// call returns.
+pub mod error;
+pub use error::Error;
+
+/// LLVM Context
+///
+/// Owns the types and constants of the modules
+/// created within it.
+pub mod context;
+
+/// LLVM Types
+///
+/// Handles to the types values can have.
+pub mod types;
+
+/// LLVM Values
+///
+/// Handles to constants, arguments, and
+/// instructions.
+pub mod value;
+
+/// LLVM Constants
+///
+/// Constant values and constant expressions
+/// built from Types and Values.
+pub mod constant;
+
+/// LLVM Functions
+///
+/// Functions within a module, their
+/// parameters, and their basic blocks.
+pub mod function;
+
+/// LLVM Global Variables
+///
+/// Module level variables, their linkage,
+/// visibility, and storage.
+pub mod global;
+
+/// LLVM Basic Blocks
+///
+/// Straight line runs of instructions
+/// within a function.
+pub mod block;
+
+/// LLVM IR Builder
+///
+/// Emits instructions into basic blocks.
+pub mod builder;
+
 /// LLVM Named Buffers
 ///
 /// For holding IR and Strings
@@ -53,11 +108,23 @@ pub mod target;
 /// on the to be compiled module.
 pub mod pass;
 
+/// LLVM Pass pipelines
+///
+/// Textual descriptions of which passes to run,
+/// for reading optimization settings from config.
+pub mod pipeline;
+
+/// LLVM Execution Engines
+///
+/// Runs compiled modules within this process.
+pub mod engine;
+
 /// Used in internal data structures to keep things alive
 ///
 /// llvm_sys, nor the LLVM's C API, nor the LLVM itself are
 /// to particular on memory management. So this enum is used
 /// to keep objects alive which are tied to an LLVM pointer.
+#[derive(Clone)]
 pub enum Buffers {
     A(CString),
     B(Vec<u8>),
@@ -71,3 +138,25 @@ pub enum Buffers {
 
 
 
+
+/// Take ownership of a message the LLVM allocated
+///
+/// Copies it into a Rust `String` and frees the
+/// original with `LLVMDisposeMessage`. The pointer
+/// must have come from the LLVM and not be null.
+unsafe fn take_message(ptr: *mut c_char) -> String {
+    let s = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+    LLVMDisposeMessage(ptr);
+    s
+}
+
+/// Convert a path for passing to the LLVM
+///
+/// The LLVM wants utf-8 paths without interior
+/// null bytes.
+fn path_to_cstring(path: &Path) -> error::Result<CString> {
+    match path.to_str() {
+        Some(x) => Ok(CString::new(x)?),
+        None => Err(Error::Io(io::Error::new(io::ErrorKind::InvalidInput, "Path isn't utf-8 parsable")))
+    }
+}
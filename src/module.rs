@@ -4,55 +4,197 @@ use llvm_sys::prelude::*;
 use llvm_sys::core::*;
 use llvm_sys::analysis::*;
 use llvm_sys::bit_writer::*;
+use llvm_sys::target::*;
+use llvm_sys::linker::*;
 
 use std::ffi::{CString,CStr};
-use std::os::raw::c_char;
+use std::os::raw::{c_char,c_void};
 use std::mem;
+use std::fmt;
+use std::path::Path;
+use std::io::Write;
+use std::marker::PhantomData;
 
-use super::Buffers;
+use super::{Buffers,take_message,path_to_cstring};
+use super::error::{Error,Result};
 use super::buffer::Buffer;
-use super::target::get_local_triple;
-
-const NULLPTR: &'static str = "
-Module name has a null ptr
-";
+use super::context::{Context,Brand};
+use super::types::Type;
+use super::function::{Function,Functions};
+use super::global::{GlobalVariable,Globals};
+use super::target::{get_local_triple,TargetData,TargetMachine};
 
 /// Abstruction around llvm:Module
 ///
 /// Module is a unit of code compilation
-pub struct Module {
+///
+/// The lifetime ties a Module to the `Context`
+/// it was created in. Modules created with
+/// `Module::new` live in the global context
+/// which is never freed.
+pub struct Module<'ctx> {
     data: LLVMModuleRef,
-    buffers: Vec<Buffers>
+    buffers: Vec<Buffers>,
+    context: Brand<'ctx>
 }
-impl Drop for Module {
+impl<'ctx> Drop for Module<'ctx> {
     fn drop(&mut self) {
         unsafe{
             LLVMDisposeModule(self.data);
         }
     }
 }
-impl Module {
+impl Module<'static> {
 
   ///Create new Module with a name
-  pub fn new<S: Into<Vec<u8>>>(name: S) -> Module {
-      let name = CString::new(name).expect(NULLPTR);
+  ///
+  /// The module is created within the LLVM's global context
+  pub fn new<S: Into<Vec<u8>>>(name: S) -> Result<Module<'static>> {
+      let name = CString::new(name)?;
         unsafe {
             let n_ptr = name.as_ptr() as *const c_char;
             let m = LLVMModuleCreateWithName(n_ptr);
-            Module {
-                data: m,
-                buffers: vec![Buffers::A(name)]
+            Ok(Module::from_raw(m, vec![Buffers::A(name)]))
+        }
+    }
+}
+impl<'ctx> Module<'ctx> {
+
+    /// Create new Module with a name within a Context
+    pub fn new_in<S: Into<Vec<u8>>>(ctx: &'ctx Context, name: S) -> Result<Module<'ctx>> {
+        let name = CString::new(name)?;
+        unsafe {
+            let n_ptr = name.as_ptr() as *const c_char;
+            let m = LLVMModuleCreateWithNameInContext(n_ptr, ctx.inner());
+            Ok(Module::from_raw(m, vec![Buffers::A(name)]))
+        }
+    }
+
+    /// Add a function
+    ///
+    /// `ty` must be a function type. The function
+    /// starts out as a declaration until basic
+    /// blocks are appended to it.
+    ///
+    /// The function borrows this module, so adding
+    /// doesn't need `&mut self` and many may be
+    /// held at once.
+    pub fn add_function<'m, S: AsRef<str>>(&'m self, name: S, ty: Type<'ctx>) -> Result<Function<'m, 'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            Ok(Function::from_raw(LLVMAddFunction(self.data, name.as_ptr(), ty.inner())))
+        }
+    }
+
+    /// Look up a function by name
+    pub fn get_function<'m, S: AsRef<str>>(&'m self, name: S) -> Option<Function<'m, 'ctx>> {
+        let name = match CString::new(name.as_ref()) {
+            Ok(x) => x,
+            Err(_) => return None
+        };
+        unsafe {
+            let ptr = LLVMGetNamedFunction(self.data, name.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(Function::from_raw(ptr))
             }
         }
     }
 
+    /// Iterate over the functions in this module
+    pub fn functions<'m>(&'m self) -> Functions<'m, 'ctx> {
+        unsafe {
+            Functions::from_raw(LLVMGetFirstFunction(self.data))
+        }
+    }
+
+    /// Add a global variable
+    ///
+    /// The global starts out as an external
+    /// declaration until it is given an initializer.
+    /// Like `add_function` the global borrows this
+    /// module.
+    pub fn add_global<'m, S: AsRef<str>>(&'m self, ty: Type<'ctx>, name: S) -> Result<GlobalVariable<'m, 'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            Ok(GlobalVariable::from_raw(LLVMAddGlobal(self.data, ty.inner(), name.as_ptr())))
+        }
+    }
+
+    /// Add a global variable within an address space
+    pub fn add_global_in_address_space<'m, S: AsRef<str>>(&'m self, ty: Type<'ctx>, name: S, address_space: u32) -> Result<GlobalVariable<'m, 'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            Ok(GlobalVariable::from_raw(LLVMAddGlobalInAddressSpace(self.data, ty.inner(), name.as_ptr(), address_space)))
+        }
+    }
+
+    /// Look up a global variable by name
+    pub fn get_global<'m, S: AsRef<str>>(&'m self, name: S) -> Option<GlobalVariable<'m, 'ctx>> {
+        let name = match CString::new(name.as_ref()) {
+            Ok(x) => x,
+            Err(_) => return None
+        };
+        unsafe {
+            let ptr = LLVMGetNamedGlobal(self.data, name.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(GlobalVariable::from_raw(ptr))
+            }
+        }
+    }
+
+    /// Iterate over the global variables in this module
+    pub fn globals<'m>(&'m self) -> Globals<'m, 'ctx> {
+        unsafe {
+            Globals::from_raw(LLVMGetFirstGlobal(self.data))
+        }
+    }
+
     /// Verify Module Contents
-    pub fn verify(&self) -> Result<(), CString> {
+    ///
+    /// On failure the error holds the verifier's
+    /// description of what is wrong.
+    pub fn verify(&self) -> Result<()> {
         unsafe {
             let mut err : *mut c_char = mem::zeroed();
             let flag: i32 = LLVMVerifyModule( self.data, LLVMVerifierFailureAction::LLVMReturnStatusAction, &mut err);
+            let msg = if err.is_null() {
+                String::new()
+            } else {
+                take_message(err)
+            };
             if flag != 0 {
-                Err(CString::from_raw(err))
+                Err(Error::Verify(msg))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Print Module as textual IR
+    ///
+    /// Returns the human readable `.ll` form
+    pub fn print_to_string(&self) -> String {
+        unsafe {
+            take_message(LLVMPrintModuleToString(self.data))
+        }
+    }
+
+    /// Print Module as textual IR to a file
+    ///
+    /// The file is created or truncated.
+    pub fn print_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        use std::io;
+
+        let path = path_to_cstring(path.as_ref())?;
+        unsafe {
+            let mut err: *mut c_char = mem::zeroed();
+            let flag = LLVMPrintModuleToFile(self.data, path.as_ptr(), &mut err);
+            if flag != 0 {
+                Err(Error::Io(io::Error::new(io::ErrorKind::Other, take_message(err))))
             } else {
                 Ok(())
             }
@@ -60,6 +202,9 @@ impl Module {
     }
 
   /// Write Module to a Buffer as IR
+  ///
+  /// The buffer holds binary bitcode, for the
+  /// human readable form see `print_to_string`
   pub fn to_ir(self) -> Buffer {
         let mut s = self;
         let mut v = Vec::<Buffers>::with_capacity(0);
@@ -70,17 +215,76 @@ impl Module {
         }
     } 
   
+    /// Write Module to a Buffer as bitcode
+    ///
+    /// Same as `to_ir` but the module is kept
+    pub fn write_bitcode(&self) -> Buffer {
+        unsafe {
+            Buffer::from_raw(LLVMWriteBitcodeToMemoryBuffer(self.data), Vec::with_capacity(0))
+        }
+    }
+
+    /// Write Module to a file as bitcode
+    pub fn write_bitcode_to_path<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        use std::io;
+
+        let path = path_to_cstring(path.as_ref())?;
+        let flag = unsafe {
+            LLVMWriteBitcodeToFile(self.data, path.as_ptr())
+        };
+        if flag != 0 {
+            Err(Error::Io(io::Error::new(io::ErrorKind::Other, "LLVM failed to write bitcode")))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Write Module as bitcode to `out`
+    pub fn write_bitcode_to<W: Write>(&self, out: &mut W) -> Result<()> {
+        let buf = self.write_bitcode();
+        out.write_all(buf.as_bytes())?;
+        Ok(())
+    }
+
+    /// Write only some functions as bitcode
+    ///
+    /// Every function not named in `functions`
+    /// is written as a declaration. Globals and
+    /// types are kept as they are.
+    ///
+    /// The dropped bodies are swapped for fresh
+    /// declarations, so their personality, comdat
+    /// and metadata go with them. The result is
+    /// verified before it is written.
+    pub fn write_bitcode_subset<S: AsRef<str>>(&self, functions: &[S]) -> Result<Buffer> {
+        let subset = self.clone();
+        let dropped: Vec<LLVMValueRef> = subset.functions()
+            .filter(|func| !func.is_declaration())
+            .filter(|func| !functions.iter()
+                .any(|name| func.get_name().to_bytes() == name.as_ref().as_bytes()))
+            .map(|func| unsafe { func.inner() })
+            .collect();
+        for func in dropped {
+            unsafe {
+                replace_with_declaration(subset.data, func);
+            }
+        }
+        subset.verify()?;
+        Ok(subset.write_bitcode())
+    }
+
     /// Set Target Triple
     ///
     /// Set what type of machine/os this module is being
     /// compiled too
-    pub fn set_target<S: AsRef<str>>(&mut self, triple: S) {
+    pub fn set_target<S: AsRef<str>>(&mut self, triple: S) -> Result<()> {
         let triple = triple.as_ref();
-        let buf = CString::new(triple).expect(NULLPTR);
+        let buf = CString::new(triple)?;
         unsafe{  
             LLVMSetTarget(self.data, buf.as_ptr());
         }
         self.buffers.push(Buffers::A(buf));
+        Ok(())
     }
 
     /// Set _This_ triple
@@ -96,14 +300,76 @@ impl Module {
         self.buffers.push(Buffers::A(buf));
     } 
   
+    /// The target triple of this module
+    ///
+    /// Empty if none was set
+    pub fn target_triple(&self) -> &CStr {
+        unsafe {
+            CStr::from_ptr(LLVMGetTarget(self.data))
+        }
+    }
+
+    /// The data layout of this module as a string
+    ///
+    /// Empty if none was set
+    pub fn data_layout_str(&self) -> &CStr {
+        unsafe {
+            CStr::from_ptr(LLVMGetDataLayoutStr(self.data))
+        }
+    }
+
+    /// Set the data layout from a string
+    ///
+    /// Only fails if `layout` contains a null byte.
+    /// Like `TargetData::new` the LLVM aborts the
+    /// process on a malformed layout, so prefer
+    /// `set_data_layout_from` when there's a
+    /// `TargetMachine` to take it from.
+    pub fn set_data_layout<S: AsRef<str>>(&mut self, layout: S) -> Result<()> {
+        let layout = CString::new(layout.as_ref())?;
+        unsafe {
+            LLVMSetDataLayout(self.data, layout.as_ptr());
+        }
+        Ok(())
+    }
+
+    /// Set the data layout to that of `machine`
+    pub fn set_data_layout_from(&mut self, machine: &TargetMachine) {
+        let layout = machine.target_data();
+        unsafe {
+            LLVMSetModuleDataLayout(self.data, layout.inner());
+        }
+    }
+
+    /// Set both the target triple and data layout
+    /// to those of `machine`
+    ///
+    /// Do this before optimizing so the passes
+    /// know what they're optimizing for.
+    pub fn configure_for(&mut self, machine: &TargetMachine) {
+        let triple = machine.get_triple();
+        unsafe {
+            LLVMSetTarget(self.data, triple.as_ptr());
+        }
+        self.set_data_layout_from(machine);
+    }
+
+    /// The data layout of this module
+    ///
+    /// Borrowed from the module, changing the
+    /// module's layout isn't possible while it
+    /// is held.
+    pub fn data_layout<'m>(&'m self) -> TargetData<'m> {
+        unsafe {
+            TargetData::from_raw(LLVMGetModuleDataLayout(self.data), false)
+        }
+    }
+
     /// Get the name of this item
-    pub fn get_name<'a>(&self) -> &'a CStr {
+    pub fn get_name(&self) -> &CStr {
         unsafe {
-            let ptr = LLVMGetValueName(mem::transmute(self.data));
-            let val: usize = mem::transmute(ptr);
-            if val == 0 {
-                panic!("Module Name returned a null ptr");
-            }
+            let mut len = 0usize;
+            let ptr = LLVMGetModuleIdentifier(self.data, &mut len);
             CStr::from_ptr(ptr)
         }
     }
@@ -111,10 +377,11 @@ impl Module {
     /// From Raw
     ///
     /// Unsafely construct this object
-    pub fn from_raw(x: LLVMModuleRef, buffers: Vec<Buffers>) -> Module {
+    pub fn from_raw(x: LLVMModuleRef, buffers: Vec<Buffers>) -> Module<'ctx> {
         Module {
             data: x,
-            buffers: buffers
+            buffers: buffers,
+            context: PhantomData
         }
     } 
 
@@ -131,6 +398,67 @@ impl Module {
         self.buffers.append(b);
     }
 
+    /// Link `other` into this module
+    ///
+    /// `other` is consumed either way, the buffers
+    /// it kept alive are kept alive by this module.
+    /// Fails if the modules are in different
+    /// contexts or define the same symbol.
+    pub fn link(&mut self, other: Module<'ctx>) -> Result<()> {
+        unsafe {
+            let ctx = LLVMGetModuleContext(self.data);
+            if ctx != LLVMGetModuleContext(other.data) {
+                return Err(Error::Link("modules are in different contexts".to_string()));
+            }
+            let (data, mut buffers) = other.split();
+            let mut messages = Vec::<String>::new();
+            let flag = with_diagnostics(ctx, &mut messages, || LLVMLinkModules2(self.data, data));
+            self.buffers.append(&mut buffers);
+            if flag != 0 {
+                Err(Error::Link(messages.join("\n")))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Copy this module into another context
+    ///
+    /// Round trips through bitcode, as the LLVM can't
+    /// clone directly across contexts.
+    pub fn clone_into<'b>(&self, ctx: &'b Context) -> Result<Module<'b>> {
+        let mut module = unsafe {
+            let buf = LLVMWriteBitcodeToMemoryBuffer(self.data);
+            Buffer::from_raw(buf, Vec::with_capacity(0)).parse_ir_in(ctx)?
+        };
+        let name = self.get_name().to_bytes();
+        unsafe {
+            LLVMSetModuleIdentifier(module.data, name.as_ptr() as *const c_char, name.len());
+        }
+        module.buffers = self.buffers.clone();
+        Ok(module)
+    }
+
+    /// Internal Method used for handing Modules
+    /// to objects that take ownership of them
+    ///
+    /// The caller becomes responsible for
+    /// disposing of the module.
+    pub unsafe fn split(self) -> (LLVMModuleRef, Vec<Buffers>) {
+        let mut s = self;
+        let mut v = Vec::<Buffers>::with_capacity(0);
+        mem::swap(&mut v, &mut s.buffers);
+        let data = s.data;
+        mem::forget(s);
+        (data, v)
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&self) -> LLVMModuleRef {
+        self.data
+    }
+
     /// Raw Module
     ///
     /// Returns a raw pointer to the underlying data type
@@ -139,3 +467,189 @@ impl Module {
     }
 }
 
+/// Collect the error messages a context reports
+/// while running `f`
+///
+/// Without a handler the LLVM prints errors and
+/// exits the process. The previous handler is
+/// put back afterwards.
+unsafe fn with_diagnostics<R, F: FnOnce() -> R>(ctx: LLVMContextRef, messages: &mut Vec<String>, f: F) -> R {
+    // the handler may be null, which the llvm_sys
+    // signatures don't allow for
+    type Handler = Option<LLVMDiagnosticHandler>;
+    type GetHandler = unsafe extern "C" fn(LLVMContextRef) -> Handler;
+    type SetHandler = unsafe extern "C" fn(LLVMContextRef, Handler, *mut c_void);
+
+    extern "C" fn collect(info: LLVMDiagnosticInfoRef, messages: *mut c_void) {
+        unsafe {
+            if let LLVMDiagnosticSeverity::LLVMDSError = LLVMGetDiagInfoSeverity(info) {
+                let messages = &mut *(messages as *mut Vec<String>);
+                messages.push(take_message(LLVMGetDiagInfoDescription(info)));
+            }
+        }
+    }
+
+    let get: GetHandler = mem::transmute(LLVMContextGetDiagnosticHandler as unsafe extern "C" fn(LLVMContextRef) -> LLVMDiagnosticHandler);
+    let set: SetHandler = mem::transmute(LLVMContextSetDiagnosticHandler as unsafe extern "C" fn(LLVMContextRef, LLVMDiagnosticHandler, *mut c_void));
+    let old_handler = get(ctx);
+    let old_context = LLVMContextGetDiagnosticContext(ctx);
+    set(ctx, Some(collect), messages as *mut Vec<String> as *mut c_void);
+    let r = f();
+    set(ctx, old_handler, old_context);
+    r
+}
+
+/// Swap a defined function for a bare declaration
+///
+/// The declaration keeps the name, type, calling
+/// convention and attributes, and takes over every
+/// use of `func`, which is then freed.
+unsafe fn replace_with_declaration(module: LLVMModuleRef, func: LLVMValueRef) {
+    let name = CStr::from_ptr(LLVMGetValueName(func)).to_owned();
+    LLVMSetValueName(func, b"\0".as_ptr() as *const c_char);
+    let decl = LLVMAddFunction(module, name.as_ptr(), LLVMGetElementType(LLVMTypeOf(func)));
+    LLVMSetFunctionCallConv(decl, LLVMGetFunctionCallConv(func));
+    LLVMSetVisibility(decl, LLVMGetVisibility(func));
+    LLVMSetDLLStorageClass(decl, LLVMGetDLLStorageClass(func));
+    LLVMSetUnnamedAddr(decl, LLVMHasUnnamedAddr(func));
+    let gc = LLVMGetGC(func);
+    if !gc.is_null() {
+        LLVMSetGC(decl, gc);
+    }
+    let indices = Some(LLVMAttributeFunctionIndex).into_iter()
+        .chain(LLVMAttributeReturnIndex..LLVMCountParams(func) + 1);
+    for index in indices {
+        let len = LLVMGetAttributeCountAtIndex(func, index) as usize;
+        let mut attrs: Vec<LLVMAttributeRef> = Vec::with_capacity(len);
+        LLVMGetAttributesAtIndex(func, index, attrs.as_mut_ptr());
+        attrs.set_len(len);
+        for attr in attrs {
+            LLVMAddAttributeAtIndex(decl, index, attr);
+        }
+    }
+    LLVMReplaceAllUsesWith(func, decl);
+    LLVMDeleteFunction(func);
+}
+
+impl<'ctx> Clone for Module<'ctx> {
+    /// Deep copy within the same context
+    fn clone(&self) -> Module<'ctx> {
+        unsafe {
+            Module::from_raw(LLVMCloneModule(self.data), self.buffers.clone())
+        }
+    }
+}
+impl<'ctx> fmt::Display for Module<'ctx> {
+    /// Prints the textual IR
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.print_to_string())
+    }
+}
+impl<'ctx> fmt::Debug for Module<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse<'ctx>(ctx: &'ctx Context, ir: &str, name: &str) -> Module<'ctx> {
+        Buffer::from_owned(ir.as_bytes().to_vec(), name).unwrap()
+            .parse_assembly(ctx).unwrap()
+    }
+
+    #[test]
+    fn bitcode_subset_keeps_named_bodies() {
+        let ctx = Context::new();
+        let module = parse(&ctx, concat!(
+            "$drop_comdat = comdat any\n",
+            "declare i32 @__gxx_personality_v0(...)\n",
+            "define void @keep() {\n",
+            "  call void @drop_comdat()\n",
+            "  call void @drop_personality()\n",
+            "  call void @drop_debug()\n",
+            "  ret void\n",
+            "}\n",
+            "define void @drop_comdat() comdat {\n",
+            "  ret void\n",
+            "}\n",
+            "define void @drop_personality() personality i32 (...)* @__gxx_personality_v0 {\n",
+            "  ret void\n",
+            "}\n",
+            "define void @drop_debug() !dbg !4 {\n",
+            "  ret void, !dbg !6\n",
+            "}\n",
+            "!llvm.dbg.cu = !{!1}\n",
+            "!llvm.module.flags = !{!0}\n",
+            "!0 = !{i32 2, !\"Debug Info Version\", i32 3}\n",
+            "!1 = distinct !DICompileUnit(language: DW_LANG_C99, file: !2, producer: \"test\", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)\n",
+            "!2 = !DIFile(filename: \"subset.c\", directory: \"/\")\n",
+            "!3 = !{null}\n",
+            "!4 = distinct !DISubprogram(name: \"drop_debug\", scope: !2, file: !2, line: 1, type: !5, isLocal: false, isDefinition: true, scopeLine: 1, unit: !1)\n",
+            "!5 = !DISubroutineType(types: !3)\n",
+            "!6 = !DILocation(line: 1, column: 1, scope: !4)\n"), "subset.ll");
+        let subset = module.write_bitcode_subset(&["keep"]).unwrap()
+            .parse_ir_in(&ctx).unwrap();
+        subset.verify().unwrap();
+        assert!(!subset.get_function("keep").unwrap().is_declaration());
+        for name in &["drop_comdat", "drop_personality", "drop_debug"] {
+            assert!(subset.get_function(name).unwrap().is_declaration());
+        }
+        // the original is untouched
+        assert!(!module.get_function("drop_debug").unwrap().is_declaration());
+    }
+
+    #[test]
+    fn link_modules() {
+        let ctx = Context::new();
+        let mut module = parse(&ctx, "declare i32 @answer()\ndefine i32 @main() {\n  %x = call i32 @answer()\n  ret i32 %x\n}\n", "main.ll");
+        let other = parse(&ctx, "define i32 @answer() {\n  ret i32 42\n}\n", "answer.ll");
+        module.link(other).unwrap();
+        module.verify().unwrap();
+        assert!(!module.get_function("answer").unwrap().is_declaration());
+        assert!(!module.get_function("main").unwrap().is_declaration());
+    }
+
+    #[test]
+    fn link_duplicate_definition() {
+        let ctx = Context::new();
+        let mut module = parse(&ctx, "define void @twice() {\n  ret void\n}\n", "a.ll");
+        let other = parse(&ctx, "define void @twice() {\n  ret void\n}\n", "b.ll");
+        match module.link(other) {
+            Err(Error::Link(msg)) => assert!(msg.contains("twice"), "{}", msg),
+            r => panic!("expected a link error, got {:?}", r)
+        }
+    }
+
+    #[test]
+    fn link_across_contexts() {
+        let a = Context::new();
+        let b = Context::new();
+        let mut module = Module::new_in(&a, "a").unwrap();
+        let other = Module::new_in(&b, "b").unwrap();
+        match module.link(other) {
+            Err(Error::Link(_)) => {}
+            r => panic!("expected a link error, got {:?}", r)
+        }
+    }
+
+    #[test]
+    fn clone_is_independent() {
+        let ctx = Context::new();
+        let module = parse(&ctx, "define void @main() {\n  ret void\n}\n", "main.ll");
+        let before = module.print_to_string();
+        let copy = module.clone();
+        let void = Type::void(&ctx);
+        copy.add_function("extra", Type::function(void, &[], false)).unwrap();
+        assert!(copy.get_function("extra").is_some());
+        assert!(module.get_function("extra").is_none());
+        assert_eq!(module.print_to_string(), before);
+
+        let other = Context::new();
+        let moved = module.clone_into(&other).unwrap();
+        assert_eq!(moved.get_name(), module.get_name());
+        assert!(moved.get_function("main").is_some());
+    }
+}
@@ -6,22 +6,73 @@ use llvm_sys::core::*;
 use llvm_sys::transforms::vectorize::*;
 use llvm_sys::transforms::scalar::*;
 use llvm_sys::transforms::ipo::*;
+use llvm_sys::transforms::pass_manager_builder::*;
 
 use std::default::Default;
+use std::marker::PhantomData;
+use std::slice;
+use std::fmt;
+
+use super::module::Module;
+use super::function::Function;
+use super::pipeline::{Pass,Pipeline};
 
 macro_rules! pass {
-    ($name: ident, $pass: ident) => {
+    ($name: ident, $variant: ident, $pass: ident) => {
         pub fn $name(&mut self) {
             unsafe{
                 $pass(self.data);
             }
+            self.passes.push(Pass::$variant);
         }
     }
 }
 
+/// Passes that work on a single function at
+/// a time, these are shared by both managers.
+macro_rules! scalar_passes {
+    () => {
+        pass!(aggressive_dce,AggressiveDCE,LLVMAddAggressiveDCEPass);
+        pass!(alignment_from_assumptions,AlignmentFromAssumptions,LLVMAddAlignmentFromAssumptionsPass);
+        pass!(cfg_simplification,CFGSimplification,LLVMAddCFGSimplificationPass);
+        pass!(constant_propigation,ConstantPropagation,LLVMAddConstantPropagationPass);
+        pass!(correlated_value_propagation,CorrelatedValuePropagation,LLVMAddCorrelatedValuePropagationPass);
+        pass!(dead_store_elimination,DeadStoreElimination,LLVMAddDeadStoreEliminationPass);
+        pass!(demote_memory_to_register,DemoteMemoryToRegister,LLVMAddDemoteMemoryToRegisterPass);
+        pass!(early_cse,EarlyCSE,LLVMAddEarlyCSEPass);
+        pass!(gvn,GVN,LLVMAddGVNPass);
+        pass!(ind_var_simplify,IndVarSimplify,LLVMAddIndVarSimplifyPass);
+        pass!(instruction_combining,InstructionCombining,LLVMAddInstructionCombiningPass);
+        pass!(jump_threading,JumpThreading,LLVMAddJumpThreadingPass);
+        pass!(licm,LICM,LLVMAddLICMPass);
+        pass!(loop_deletion,LoopDeletion,LLVMAddLoopDeletionPass);
+        pass!(loop_reroll,LoopReroll,LLVMAddLoopRerollPass);
+        pass!(loop_rotate,LoopRotate,LLVMAddLoopRotatePass);
+        pass!(loop_unswitch,LoopUnswitch,LLVMAddLoopUnswitchPass);
+        pass!(lower_expect_intrinsic,LowerExpectIntrinsic,LLVMAddLowerExpectIntrinsicPass);
+        pass!(lower_switch,LowerSwitch,LLVMAddLowerSwitchPass);
+        pass!(memcpy_opt,MemCpyOpt,LLVMAddMemCpyOptPass);
+        pass!(merge_load_store_motion,MergedLoadStoreMotion,LLVMAddMergedLoadStoreMotionPass);
+        pass!(partially_inline_lib_calls,PartiallyInlineLibCalls,LLVMAddPartiallyInlineLibCallsPass);
+        pass!(promote_memory_to_register,PromoteMemoryToRegister,LLVMAddPromoteMemoryToRegisterPass);
+        pass!(reassociate,Reassociate,LLVMAddReassociatePass);
+        pass!(sccp,SCCP,LLVMAddSCCPPass);
+        pass!(scalarizer,Scalarizer,LLVMAddScalarizerPass);
+        pass!(scoped_no_alias_aa,ScopedNoAliasAA,LLVMAddScopedNoAliasAAPass);
+        pass!(simplify_lib_calls,SimplifyLibCalls,LLVMAddSimplifyLibCallsPass);
+        pass!(tail_call_elimination,TailCallElimination,LLVMAddTailCallEliminationPass);
+        pass!(verifer,Verifier,LLVMAddVerifierPass);
+    }
+}
+
 /// Manages code gen passes
+///
+/// Every pass added through its methods is
+/// recorded, see `passes`. Passes added to
+/// `inner` directly are not.
 pub struct PassManager {
-    data: LLVMPassManagerRef
+    data: LLVMPassManagerRef,
+    passes: Vec<Pass>
 }
 impl Drop for PassManager {
     fn drop(&mut self) {
@@ -36,65 +87,71 @@ impl PassManager {
     pub fn new() -> PassManager {
         unsafe {
             PassManager {
-                data: LLVMCreatePassManager()
+                data: LLVMCreatePassManager(),
+                passes: Vec::new()
             }
         }
     }
+
+    /// Creates a Pass Manager holding the standard
+    /// pipeline for an optimization level
+    ///
+    /// `PassManager::for_level(OptLevel::O2)` gives
+    /// roughly what `clang -O2` runs over a module.
+    pub fn for_level(level: OptLevel) -> PassManager {
+        let mut mngr = PassManager::new();
+        level.populate_module(&mut mngr);
+        mngr
+    }
   
 
     /// Gives the option to the end developer to
     /// handle some optimizations more eloquently
     pub fn apply_opt<T: ApplyOpt>(&mut self, opt: T) {
         opt.add_pass(self);
+        if let Some(pass) = opt.as_pass() {
+            self.passes.push(pass);
+        }
     }
 
-	pass!(aggressive_dce,LLVMAddAggressiveDCEPass);
-	pass!(alignment_from_assumptions,LLVMAddAlignmentFromAssumptionsPass);
-	pass!(cfg_simplification,LLVMAddCFGSimplificationPass);
-	pass!(constant_propigation,LLVMAddConstantPropagationPass);
-	pass!(correlated_value_propagation,LLVMAddCorrelatedValuePropagationPass);
-	pass!(dead_store_elimination,LLVMAddDeadStoreEliminationPass);
-	pass!(demote_memory_to_register,LLVMAddDemoteMemoryToRegisterPass);
-	pass!(early_cse,LLVMAddEarlyCSEPass);
-	pass!(gvn,LLVMAddGVNPass);
-	pass!(ind_var_simplify,LLVMAddIndVarSimplifyPass);
-	pass!(instruction_combining,LLVMAddInstructionCombiningPass);
-	pass!(jump_threading,LLVMAddJumpThreadingPass);
-	pass!(licm,LLVMAddLICMPass);
-	pass!(loop_deletion,LLVMAddLoopDeletionPass);
-	pass!(loop_reroll,LLVMAddLoopRerollPass);
-	pass!(loop_rotate,LLVMAddLoopRotatePass);
-	pass!(loop_unswitch,LLVMAddLoopUnswitchPass);
-	pass!(lower_expect_intrinsic,LLVMAddLowerExpectIntrinsicPass);
-	pass!(lower_switch,LLVMAddLowerSwitchPass);
-	pass!(memcpy_opt,LLVMAddMemCpyOptPass);
-	pass!(merge_load_store_motion,LLVMAddMergedLoadStoreMotionPass);
-	pass!(partially_inline_lib_calls,LLVMAddPartiallyInlineLibCallsPass);
-	pass!(promote_memory_to_register,LLVMAddPromoteMemoryToRegisterPass);
-	pass!(reassociate,LLVMAddReassociatePass);
-	pass!(sccp,LLVMAddSCCPPass);
-	pass!(scalarizer,LLVMAddScalarizerPass);
-	pass!(scoped_no_alias_aa,LLVMAddScopedNoAliasAAPass);
-	pass!(simplify_lib_calls,LLVMAddSimplifyLibCallsPass);
-	pass!(tail_call_elimination,LLVMAddTailCallEliminationPass);
-	pass!(verifer,LLVMAddVerifierPass);
-	pass!(always_inliner,LLVMAddAlwaysInlinerPass);
-	pass!(argument_promotion,LLVMAddArgumentPromotionPass);
-	pass!(constant_merge,LLVMAddConstantMergePass);
-	pass!(dead_arg_elimination,LLVMAddDeadArgEliminationPass);
-	pass!(function_attrs,LLVMAddFunctionAttrsPass);
-	pass!(global_dce,LLVMAddGlobalDCEPass);
-	pass!(global_optimizer,LLVMAddGlobalOptimizerPass);
-	pass!(ip_constant_propagation,LLVMAddIPConstantPropagationPass);
-	pass!(ipsccp,LLVMAddIPSCCPPass);
-	pass!(prune,LLVMAddPruneEHPass);
-	pass!(strip_dead_prototypes,LLVMAddStripDeadPrototypesPass);
-	pass!(strip_symbols,LLVMAddStripSymbolsPass);
+    scalar_passes!();
+    pass!(always_inliner,AlwaysInliner,LLVMAddAlwaysInlinerPass);
+    pass!(argument_promotion,ArgumentPromotion,LLVMAddArgumentPromotionPass);
+    pass!(constant_merge,ConstantMerge,LLVMAddConstantMergePass);
+    pass!(dead_arg_elimination,DeadArgElimination,LLVMAddDeadArgEliminationPass);
+    pass!(function_attrs,FunctionAttrs,LLVMAddFunctionAttrsPass);
+    pass!(global_dce,GlobalDCE,LLVMAddGlobalDCEPass);
+    pass!(global_optimizer,GlobalOptimizer,LLVMAddGlobalOptimizerPass);
+    pass!(ip_constant_propagation,IPConstantPropagation,LLVMAddIPConstantPropagationPass);
+    pass!(ipsccp,IPSCCP,LLVMAddIPSCCPPass);
+    pass!(prune,PruneEH,LLVMAddPruneEHPass);
+    pass!(strip_dead_prototypes,StripDeadPrototypes,LLVMAddStripDeadPrototypesPass);
+    pass!(strip_symbols,StripSymbols,LLVMAddStripSymbolsPass);
   
     pub fn internalize_pass(&mut self, all_but_main: u32) {
         unsafe{
             LLVMAddInternalizePass(self.data, all_but_main);
         }
+        self.passes.push(Pass::Internalize(all_but_main));
+    }
+
+    /// The passes added so far, in order
+    pub fn passes(&self) -> slice::Iter<'_, Pass> {
+        self.passes.iter()
+    }
+
+    /// The passes added so far as a `Pipeline`
+    pub fn pipeline(&self) -> Pipeline {
+        Pipeline::from(self.passes.clone())
+    }
+
+    /// Run every pass over `module`
+    ///
+    /// Returns `true` if any pass modified it
+    pub fn run(&mut self, module: &mut Module) -> bool {
+        unsafe {
+            LLVMRunPassManager(self.data, module.inner()) != 0
+        }
     }
 
     /// Allows access to inner data field
@@ -103,11 +160,327 @@ impl PassManager {
         self.data
     }
 }
+impl Clone for PassManager {
+    /// Replays the recorded passes into a new manager
+    fn clone(&self) -> PassManager {
+        let mut mngr = PassManager::new();
+        for pass in self.passes.iter() {
+            pass.add_to(&mut mngr);
+        }
+        mngr
+    }
+}
+impl fmt::Debug for PassManager {
+    /// Prints the recorded pipeline
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PassManager(\"{}\")", self.pipeline())
+    }
+}
+
+/// Manages passes over individual functions
+///
+/// Bound to the `Module` it was created for,
+/// only functions within it may be run on.
+/// Call `initialize` before `run_on`, and
+/// `finalize` once done.
+pub struct FunctionPassManager<'m, 'ctx: 'm> {
+    data: LLVMPassManagerRef,
+    passes: Vec<Pass>,
+    module: PhantomData<&'m Module<'ctx>>
+}
+impl<'m, 'ctx> Drop for FunctionPassManager<'m, 'ctx> {
+    fn drop(&mut self) {
+        unsafe {
+            LLVMDisposePassManager(self.data);
+        }
+    }
+}
+impl<'m, 'ctx> FunctionPassManager<'m, 'ctx> {
+
+    /// Creates a new empty Function Pass Manager
+    /// for the functions within `module`
+    pub fn new(module: &'m Module<'ctx>) -> FunctionPassManager<'m, 'ctx> {
+        unsafe {
+            FunctionPassManager {
+                data: LLVMCreateFunctionPassManagerForModule(module.inner()),
+                passes: Vec::new(),
+                module: PhantomData
+            }
+        }
+    }
+
+    /// Creates a Function Pass Manager holding the
+    /// standard per function pipeline for an
+    /// optimization level
+    pub fn for_level(module: &'m Module<'ctx>, level: OptLevel) -> FunctionPassManager<'m, 'ctx> {
+        let mut mngr = FunctionPassManager::new(module);
+        level.populate_function(&mut mngr);
+        mngr
+    }
+
+    /// Run the initializers of every pass
+    ///
+    /// Returns `true` if any of them modified
+    /// the module
+    pub fn initialize(&mut self) -> bool {
+        unsafe {
+            LLVMInitializeFunctionPassManager(self.data) != 0
+        }
+    }
+
+    /// Run every pass over `func`
+    ///
+    /// Returns `true` if any pass modified it
+    pub fn run_on(&mut self, func: &Function<'m, 'ctx>) -> bool {
+        unsafe {
+            LLVMRunFunctionPassManager(self.data, func.inner()) != 0
+        }
+    }
+
+    /// Run the finalizers of every pass
+    ///
+    /// Returns `true` if any of them modified
+    /// the module
+    pub fn finalize(&mut self) -> bool {
+        unsafe {
+            LLVMFinalizeFunctionPassManager(self.data) != 0
+        }
+    }
+
+    scalar_passes!();
+
+    /// The passes added so far, in order
+    pub fn passes(&self) -> slice::Iter<'_, Pass> {
+        self.passes.iter()
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&mut self) -> LLVMPassManagerRef {
+        self.data
+    }
+}
+
+
+/// Standard optimization pipelines
+///
+/// Drives the LLVM's `PassManagerBuilder` the
+/// same way clang does for each `-O` flag. Start
+/// from one of the levels and adjust it with the
+/// setters.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct OptLevel {
+    opt: u32,
+    size: u32,
+    inliner: Option<u32>,
+    unroll_loops: bool,
+    loop_vectorize: bool,
+    slp_vectorize: bool
+}
+impl OptLevel {
 
+    /// No optimization `-O0`
+    pub const O0: OptLevel = OptLevel {
+        opt: 0,
+        size: 0,
+        inliner: None,
+        unroll_loops: false,
+        loop_vectorize: false,
+        slp_vectorize: false
+    };
+
+    /// Light optimization `-O1`
+    pub const O1: OptLevel = OptLevel {
+        opt: 1,
+        size: 0,
+        inliner: None,
+        unroll_loops: false,
+        loop_vectorize: false,
+        slp_vectorize: false
+    };
+
+    /// Default optimization `-O2`
+    pub const O2: OptLevel = OptLevel {
+        opt: 2,
+        size: 0,
+        inliner: Some(225),
+        unroll_loops: true,
+        loop_vectorize: true,
+        slp_vectorize: true
+    };
+
+    /// Aggressive optimization `-O3`
+    pub const O3: OptLevel = OptLevel {
+        opt: 3,
+        size: 0,
+        inliner: Some(250),
+        unroll_loops: true,
+        loop_vectorize: true,
+        slp_vectorize: true
+    };
+
+    /// Optimize for size `-Os`
+    #[allow(non_upper_case_globals)]
+    pub const Os: OptLevel = OptLevel {
+        opt: 2,
+        size: 1,
+        inliner: Some(75),
+        unroll_loops: true,
+        loop_vectorize: true,
+        slp_vectorize: false
+    };
+
+    /// Aggressively optimize for size `-Oz`
+    #[allow(non_upper_case_globals)]
+    pub const Oz: OptLevel = OptLevel {
+        opt: 2,
+        size: 2,
+        inliner: Some(25),
+        unroll_loops: true,
+        loop_vectorize: false,
+        slp_vectorize: false
+    };
+
+    /// The optimization level, 0 to 3
+    pub fn opt_level(&self) -> u32 {
+        self.opt
+    }
+
+    /// The size level, 0 to 2
+    pub fn size_level(&self) -> u32 {
+        self.size
+    }
+
+    /// The inliner threshold
+    ///
+    /// `None` means only `alwaysinline` functions
+    /// are inlined.
+    pub fn inliner_threshold(&self) -> Option<u32> {
+        self.inliner
+    }
+
+    /// Are loops unrolled
+    pub fn unroll_loops(&self) -> bool {
+        self.unroll_loops
+    }
+
+    /// Is the loop vectorizer run
+    pub fn loop_vectorize(&self) -> bool {
+        self.loop_vectorize
+    }
+
+    /// Is the SLP vectorizer run
+    pub fn slp_vectorize(&self) -> bool {
+        self.slp_vectorize
+    }
+
+    pub fn set_opt_level(&mut self, level: u32) -> &mut Self {
+        self.opt = level;
+        self
+    }
+
+    pub fn set_size_level(&mut self, level: u32) -> &mut Self {
+        self.size = level;
+        self
+    }
+
+    pub fn set_inliner_threshold(&mut self, threshold: Option<u32>) -> &mut Self {
+        self.inliner = threshold;
+        self
+    }
+
+    pub fn set_unroll_loops(&mut self, flag: bool) -> &mut Self {
+        self.unroll_loops = flag;
+        self
+    }
+
+    pub fn set_loop_vectorize(&mut self, flag: bool) -> &mut Self {
+        self.loop_vectorize = flag;
+        self
+    }
+
+    pub fn set_slp_vectorize(&mut self, flag: bool) -> &mut Self {
+        self.slp_vectorize = flag;
+        self
+    }
+
+    /// Add this level's module passes to `mngr`
+    ///
+    /// The LLVM-C API has no vectorization switches
+    /// so those passes are added after the rest.
+    pub fn populate_module(&self, mngr: &mut PassManager) {
+        let pmb = PassManagerBuilder::new(self);
+        unsafe {
+            if self.inliner.is_none() {
+                LLVMAddAlwaysInlinerPass(mngr.inner());
+            }
+            LLVMPassManagerBuilderPopulateModulePassManager(pmb.data, mngr.inner());
+            if self.loop_vectorize {
+                LLVMAddLoopVectorizePass(mngr.inner());
+            }
+            if self.slp_vectorize {
+                LLVMAddSLPVectorizePass(mngr.inner());
+            }
+        }
+        mngr.passes.push(Pass::Level(*self));
+    }
+
+    /// Add this level's function passes to `mngr`
+    pub fn populate_function(&self, mngr: &mut FunctionPassManager) {
+        let pmb = PassManagerBuilder::new(self);
+        unsafe {
+            LLVMPassManagerBuilderPopulateFunctionPassManager(pmb.data, mngr.inner());
+        }
+        mngr.passes.push(Pass::Level(*self));
+    }
+}
+impl Default for OptLevel {
+    /// Returns `OptLevel::O2` like clang's `-O`
+    fn default() -> OptLevel {
+        OptLevel::O2
+    }
+}
+
+/// Owns an llvm::PassManagerBuilder for as long
+/// as it takes to populate a manager
+struct PassManagerBuilder {
+    data: LLVMPassManagerBuilderRef
+}
+impl Drop for PassManagerBuilder {
+    fn drop(&mut self) {
+        unsafe {
+            LLVMPassManagerBuilderDispose(self.data);
+        }
+    }
+}
+impl PassManagerBuilder {
+    fn new(level: &OptLevel) -> PassManagerBuilder {
+        unsafe {
+            let data = LLVMPassManagerBuilderCreate();
+            LLVMPassManagerBuilderSetOptLevel(data, level.opt);
+            LLVMPassManagerBuilderSetSizeLevel(data, level.size);
+            LLVMPassManagerBuilderSetDisableUnrollLoops(data, (!level.unroll_loops) as LLVMBool);
+            if let Some(threshold) = level.inliner {
+                LLVMPassManagerBuilderUseInlinerWithThreshold(data, threshold);
+            }
+            PassManagerBuilder {
+                data: data
+            }
+        }
+    }
+}
 
 /// For enums that can apply optimizations 
 pub trait ApplyOpt {
     fn add_pass(&self, mngr: &mut PassManager);
+
+    /// How `PassManager` records this option
+    ///
+    /// Options returning `None` aren't recorded
+    /// and so aren't replayed by `clone`.
+    fn as_pass(&self) -> Option<Pass> {
+        None
+    }
 }
 
 /// Controls which `ScalarReplAggregatesPass`
@@ -115,7 +488,7 @@ pub trait ApplyOpt {
 ///
 /// you can re-call the method to apply more
 /// then one type.
-#[derive(Clone,Copy,Debug)]
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
 pub enum ScalarReplAggregates {
     /// applies no optimization pass
     None,
@@ -137,6 +510,10 @@ impl ApplyOpt for ScalarReplAggregates {
             };
         }
     }
+
+    fn as_pass(&self) -> Option<Pass> {
+        Some(Pass::ScalarReplAggregates(*self))
+    }
 }
 
 /// Vectorization Passes
@@ -144,7 +521,7 @@ impl ApplyOpt for ScalarReplAggregates {
 /// you can add multiple
 /// by re-calling the `appy_opt`
 /// value multiple times.
-#[derive(Clone,Copy,Debug)]
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
 pub enum Vectorize {
     /// applies no optimization pass
     None,
@@ -172,4 +549,8 @@ impl ApplyOpt for Vectorize {
             };
         }
     }
+
+    fn as_pass(&self) -> Option<Pass> {
+        Some(Pass::Vectorize(*self))
+    }
 }
@@ -0,0 +1,394 @@
+
+use std::fmt;
+use std::error;
+use std::str::FromStr;
+
+use super::pass::{PassManager,OptLevel,ScalarReplAggregates,Vectorize};
+use super::error::{Error,Result};
+
+macro_rules! passes {
+    ($($variant: ident, $text: tt, $method: ident;)*) => {
+
+        /// A single pass within a `Pipeline`
+        ///
+        /// Every `PassManager` method has a variant,
+        /// named after the LLVM's own name for the pass.
+        #[derive(Clone,Copy,Debug,PartialEq,Eq)]
+        pub enum Pass {
+            $($variant,)*
+            /// `internalize<all-but-main=N>`
+            Internalize(u32),
+            /// `sroa`, `sroa<ssa>`, or `sroa<threshold=N>`
+            ScalarReplAggregates(ScalarReplAggregates),
+            /// `bb-vectorize`, `loop-vectorize`, or `slp-vectorizer`
+            Vectorize(Vectorize),
+            /// A whole standard pipeline, `O2` or
+            /// `opt<level=2,size=0,...>` when adjusted
+            Level(OptLevel)
+        }
+        impl Pass {
+
+            /// Add this pass to `mngr`
+            pub fn add_to(&self, mngr: &mut PassManager) {
+                match *self {
+                    $(Pass::$variant => mngr.$method(),)*
+                    Pass::Internalize(x) => mngr.internalize_pass(x),
+                    Pass::ScalarReplAggregates(x) => mngr.apply_opt(x),
+                    Pass::Vectorize(x) => mngr.apply_opt(x),
+                    Pass::Level(x) => x.populate_module(mngr)
+                }
+            }
+
+            /// Pass without parameters
+            fn simple(name: &str) -> Option<Pass> {
+                match name {
+                    $($text => Some(Pass::$variant),)*
+                    _ => None
+                }
+            }
+
+            /// Name of a pass without parameters
+            fn simple_name(&self) -> Option<&'static str> {
+                match *self {
+                    $(Pass::$variant => Some($text),)*
+                    _ => None
+                }
+            }
+        }
+    }
+}
+
+passes!{
+    AggressiveDCE, "adce", aggressive_dce;
+    AlignmentFromAssumptions, "alignment-from-assumptions", alignment_from_assumptions;
+    CFGSimplification, "simplifycfg", cfg_simplification;
+    ConstantPropagation, "constprop", constant_propigation;
+    CorrelatedValuePropagation, "correlated-propagation", correlated_value_propagation;
+    DeadStoreElimination, "dse", dead_store_elimination;
+    DemoteMemoryToRegister, "reg2mem", demote_memory_to_register;
+    EarlyCSE, "early-cse", early_cse;
+    GVN, "gvn", gvn;
+    IndVarSimplify, "indvars", ind_var_simplify;
+    InstructionCombining, "instcombine", instruction_combining;
+    JumpThreading, "jump-threading", jump_threading;
+    LICM, "licm", licm;
+    LoopDeletion, "loop-deletion", loop_deletion;
+    LoopReroll, "loop-reroll", loop_reroll;
+    LoopRotate, "loop-rotate", loop_rotate;
+    LoopUnswitch, "loop-unswitch", loop_unswitch;
+    LowerExpectIntrinsic, "lower-expect", lower_expect_intrinsic;
+    LowerSwitch, "lowerswitch", lower_switch;
+    MemCpyOpt, "memcpyopt", memcpy_opt;
+    MergedLoadStoreMotion, "mldst-motion", merge_load_store_motion;
+    PartiallyInlineLibCalls, "partially-inline-libcalls", partially_inline_lib_calls;
+    PromoteMemoryToRegister, "mem2reg", promote_memory_to_register;
+    Reassociate, "reassociate", reassociate;
+    SCCP, "sccp", sccp;
+    Scalarizer, "scalarizer", scalarizer;
+    ScopedNoAliasAA, "scoped-noalias", scoped_no_alias_aa;
+    SimplifyLibCalls, "simplify-libcalls", simplify_lib_calls;
+    TailCallElimination, "tailcallelim", tail_call_elimination;
+    Verifier, "verify", verifer;
+    AlwaysInliner, "always-inline", always_inliner;
+    ArgumentPromotion, "argpromotion", argument_promotion;
+    ConstantMerge, "constmerge", constant_merge;
+    DeadArgElimination, "deadargelim", dead_arg_elimination;
+    FunctionAttrs, "functionattrs", function_attrs;
+    GlobalDCE, "globaldce", global_dce;
+    GlobalOptimizer, "globalopt", global_optimizer;
+    IPConstantPropagation, "ipconstprop", ip_constant_propagation;
+    IPSCCP, "ipsccp", ipsccp;
+    PruneEH, "prune-eh", prune;
+    StripDeadPrototypes, "strip-dead-prototypes", strip_dead_prototypes;
+    StripSymbols, "strip", strip_symbols;
+}
+
+impl FromStr for Pass {
+    type Err = Error;
+
+    /// Parse a single pass, `name` or `name<param>`
+    fn from_str(text: &str) -> Result<Pass> {
+        let text = text.trim();
+        let (name, param) = match text.find('<') {
+            Some(idx) if text.ends_with('>') => (&text[..idx], Some(&text[idx+1..text.len()-1])),
+            Some(_) => return Err(PipelineError::new(text, "unclosed `<`").into()),
+            None => (text, None)
+        };
+        if let Some(pass) = Pass::simple(name) {
+            return match param {
+                None => Ok(pass),
+                Some(_) => Err(PipelineError::new(text, "pass takes no parameters").into())
+            };
+        }
+        let pass = match (name, param) {
+            ("internalize", None) => Pass::Internalize(1),
+            ("internalize", Some(p)) => Pass::Internalize(parse_value(text, p, "all-but-main")?),
+            ("sroa", None) => Pass::ScalarReplAggregates(ScalarReplAggregates::Default),
+            ("sroa", Some("ssa")) => Pass::ScalarReplAggregates(ScalarReplAggregates::SSA),
+            ("sroa", Some("none")) => Pass::ScalarReplAggregates(ScalarReplAggregates::None),
+            ("sroa", Some(p)) => Pass::ScalarReplAggregates(ScalarReplAggregates::Threshold(parse_value(text, p, "threshold")?)),
+            ("bb-vectorize", None) => Pass::Vectorize(Vectorize::BB),
+            ("loop-vectorize", None) => Pass::Vectorize(Vectorize::Loop),
+            ("slp-vectorizer", None) => Pass::Vectorize(Vectorize::SLP),
+            ("vectorize", Some("none")) => Pass::Vectorize(Vectorize::None),
+            ("O0", None) => Pass::Level(OptLevel::O0),
+            ("O1", None) => Pass::Level(OptLevel::O1),
+            ("O2", None) => Pass::Level(OptLevel::O2),
+            ("O3", None) => Pass::Level(OptLevel::O3),
+            ("Os", None) => Pass::Level(OptLevel::Os),
+            ("Oz", None) => Pass::Level(OptLevel::Oz),
+            ("opt", Some(p)) => Pass::Level(parse_level(text, p)?),
+            ("", _) => return Err(PipelineError::new(text, "missing pass name").into()),
+            (_, _) => return Err(PipelineError::new(text, "unknown pass").into())
+        };
+        Ok(pass)
+    }
+}
+impl fmt::Display for Pass {
+    /// Prints the pass as `Pass::from_str` reads it
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = self.simple_name() {
+            return f.write_str(name);
+        }
+        match *self {
+            Pass::Internalize(1) => f.write_str("internalize"),
+            Pass::Internalize(x) => write!(f, "internalize<all-but-main={}>", x),
+            Pass::ScalarReplAggregates(ScalarReplAggregates::Default) => f.write_str("sroa"),
+            Pass::ScalarReplAggregates(ScalarReplAggregates::SSA) => f.write_str("sroa<ssa>"),
+            Pass::ScalarReplAggregates(ScalarReplAggregates::None) => f.write_str("sroa<none>"),
+            Pass::ScalarReplAggregates(ScalarReplAggregates::Threshold(x)) => write!(f, "sroa<threshold={}>", x),
+            Pass::Vectorize(Vectorize::BB) => f.write_str("bb-vectorize"),
+            Pass::Vectorize(Vectorize::Loop) => f.write_str("loop-vectorize"),
+            Pass::Vectorize(Vectorize::SLP) => f.write_str("slp-vectorizer"),
+            Pass::Vectorize(Vectorize::None) => f.write_str("vectorize<none>"),
+            Pass::Level(x) if x == OptLevel::O0 => f.write_str("O0"),
+            Pass::Level(x) if x == OptLevel::O1 => f.write_str("O1"),
+            Pass::Level(x) if x == OptLevel::O2 => f.write_str("O2"),
+            Pass::Level(x) if x == OptLevel::O3 => f.write_str("O3"),
+            Pass::Level(x) if x == OptLevel::Os => f.write_str("Os"),
+            Pass::Level(x) if x == OptLevel::Oz => f.write_str("Oz"),
+            Pass::Level(x) => {
+                write!(f, "opt<level={},size={},inline=", x.opt_level(), x.size_level())?;
+                match x.inliner_threshold() {
+                    Some(t) => write!(f, "{}", t)?,
+                    None => f.write_str("none")?
+                };
+                write!(f, ",unroll={},loop-vectorize={},slp-vectorize={}>",
+                    x.unroll_loops(), x.loop_vectorize(), x.slp_vectorize())
+            },
+            _ => unreachable!()
+        }
+    }
+}
+
+/// Read `key=value` out of a pass parameter
+fn parse_value<T: FromStr>(text: &str, param: &str, key: &str) -> Result<T> {
+    let mut parts = param.splitn(2, '=');
+    match (parts.next(), parts.next()) {
+        (Some(k), Some(v)) if k.trim() == key => {
+            v.trim().parse::<T>()
+                .map_err(|_| PipelineError::new(text, format!("`{}` isn't a valid `{}`", v.trim(), key)).into())
+        },
+        _ => Err(PipelineError::new(text, format!("expected `{}=`", key)).into())
+    }
+}
+
+/// Read the parameters of `opt<...>`
+///
+/// Starts from `O0`, every key is optional.
+fn parse_level(text: &str, params: &str) -> Result<OptLevel> {
+    let mut level = OptLevel::O0;
+    for param in params.split(',') {
+        let key = param.splitn(2, '=').next().unwrap_or("").trim();
+        match key {
+            "level" => { level.set_opt_level(parse_value(text, param, key)?); },
+            "size" => { level.set_size_level(parse_value(text, param, key)?); },
+            "inline" if param.trim().ends_with("=none") => { level.set_inliner_threshold(None); },
+            "inline" => { level.set_inliner_threshold(Some(parse_value(text, param, key)?)); },
+            "unroll" => { level.set_unroll_loops(parse_value(text, param, key)?); },
+            "loop-vectorize" => { level.set_loop_vectorize(parse_value(text, param, key)?); },
+            "slp-vectorize" => { level.set_slp_vectorize(parse_value(text, param, key)?); },
+            _ => return Err(PipelineError::new(text, format!("unknown parameter `{}`", key)).into())
+        };
+    }
+    Ok(level)
+}
+
+/// Split a pipeline on the commas outside of `<>`
+fn split_passes(text: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0usize;
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(&text[start..i]);
+                start = i + 1;
+            },
+            _ => { }
+        }
+    }
+    items.push(&text[start..]);
+    items
+}
+
+/// An ordered list of passes
+///
+/// Written as pass names separated by commas,
+/// `"mem2reg,instcombine,gvn,simplifycfg"`.
+/// Passes with parameters put them in angle
+/// brackets, `"sroa<threshold=128>"`.
+#[derive(Clone,Debug,Default,PartialEq,Eq)]
+pub struct Pipeline {
+    passes: Vec<Pass>
+}
+impl Pipeline {
+
+    /// An empty pipeline
+    pub fn new() -> Pipeline {
+        Pipeline {
+            passes: Vec::new()
+        }
+    }
+
+    /// Append a pass
+    pub fn push(&mut self, pass: Pass) -> &mut Self {
+        self.passes.push(pass);
+        self
+    }
+
+    /// The passes in order
+    pub fn passes(&self) -> &[Pass] {
+        &self.passes
+    }
+
+    /// Add every pass to `mngr` in order
+    pub fn apply(&self, mngr: &mut PassManager) {
+        for pass in self.passes.iter() {
+            pass.add_to(mngr);
+        }
+    }
+
+    /// Build a new `PassManager` holding every pass
+    pub fn build(&self) -> PassManager {
+        let mut mngr = PassManager::new();
+        self.apply(&mut mngr);
+        mngr
+    }
+}
+impl FromStr for Pipeline {
+    type Err = Error;
+    fn from_str(text: &str) -> Result<Pipeline> {
+        let mut pipeline = Pipeline::new();
+        if text.trim().is_empty() {
+            return Ok(pipeline);
+        }
+        for item in split_passes(text) {
+            pipeline.push(item.parse::<Pass>()?);
+        }
+        Ok(pipeline)
+    }
+}
+impl fmt::Display for Pipeline {
+    /// Prints the pipeline as `Pipeline::from_str` reads it
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, pass) in self.passes.iter().enumerate() {
+            if i != 0 {
+                f.write_str(",")?;
+            }
+            fmt::Display::fmt(pass, f)?;
+        }
+        Ok(())
+    }
+}
+impl From<Vec<Pass>> for Pipeline {
+    fn from(passes: Vec<Pass>) -> Pipeline {
+        Pipeline {
+            passes: passes
+        }
+    }
+}
+
+/// Error from parsing a pipeline
+#[derive(Clone,Debug)]
+pub struct PipelineError {
+    text: String,
+    message: String
+}
+impl PipelineError {
+    fn new<S: Into<String>>(text: &str, message: S) -> PipelineError {
+        PipelineError {
+            text: text.to_string(),
+            message: message.into()
+        }
+    }
+
+    /// The pass text that couldn't be read
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// What was wrong with it
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bad pass `{}`: {}", self.text, self.message)
+    }
+}
+impl error::Error for PipelineError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(text: &str) {
+        let pipeline = text.parse::<Pipeline>().unwrap();
+        assert_eq!(pipeline.to_string(), text);
+        assert_eq!(pipeline.to_string().parse::<Pipeline>().unwrap(), pipeline);
+    }
+
+    fn rejected(text: &str) -> bool {
+        match text.parse::<Pipeline>() {
+            Err(Error::Pipeline(_)) => true,
+            _ => false
+        }
+    }
+
+    #[test]
+    fn round_trip_passes() {
+        round_trip("mem2reg,instcombine,gvn,simplifycfg");
+        round_trip("sroa<threshold=128>,internalize<all-but-main=0>,O2");
+        round_trip("sroa<ssa>,loop-vectorize,slp-vectorizer,Oz");
+    }
+
+    #[test]
+    fn round_trip_opt_level() {
+        round_trip("opt<level=3,size=0,inline=none,unroll=false,loop-vectorize=true,slp-vectorize=true>");
+        round_trip("gvn,opt<level=1,size=2,inline=25,unroll=true,loop-vectorize=false,slp-vectorize=false>,verify");
+    }
+
+    #[test]
+    fn empty_pipeline() {
+        assert_eq!("".parse::<Pipeline>().unwrap(), Pipeline::new());
+        assert_eq!(Pipeline::new().to_string(), "");
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        assert!(rejected("gvn,"));
+        assert!(rejected("bogus"));
+        assert!(rejected("gvn<x>"));
+        assert!(rejected("sroa<threshold=1"));
+        assert!(rejected("sroa<threshold=x>"));
+        assert!(rejected("opt<level=2,bogus=1>"));
+    }
+}
@@ -4,17 +4,25 @@ use llvm_sys::*;
 use llvm_sys::prelude::*;
 use llvm_sys::core::*;
 use llvm_sys::target_machine::*;
+use llvm_sys::target::*;
 
-use super::Buffers;
+use super::{Buffers,take_message,path_to_cstring};
+use super::module::Module;
+use super::buffer::Buffer;
+use super::error::{Error,Result};
+use super::context::Context;
+use super::types::{Type,TypeKind};
 
 use std::ffi::{CString,CStr};
 use std::os::raw::c_char;
 use std::default::Default;
 use std::ptr::null_mut;
-
-const NULLPTR: &'static str = "
-Null pointer recieved by API
-";
+use std::path::Path;
+use std::fmt;
+use std::error;
+use std::mem;
+use std::sync::Once;
+use std::marker::PhantomData;
 
 /// Abstraction around llvm::TargetMachine
 ///
@@ -33,13 +41,52 @@ pub struct Target {
 impl Target {
   
     /// With the name of a target
-    pub fn from_name(name: &str) -> Target {
+    ///
+    /// Returns `None` if no registered target
+    /// has that name.
+    pub fn from_name(name: &str) -> Option<Target> {
         unsafe {
-            let cstr = CString::new(name).expect(NULLPTR);
+            let cstr = match CString::new(name) {
+                Ok(x) => x,
+                Err(_) => return None
+            };
             let tpr = LLVMGetTargetFromName(cstr.as_ptr());
-            Target {
+            if tpr.is_null() {
+                return None;
+            }
+            Some(Target {
                 data: tpr,
                 buffers: vec![Buffers::A(cstr)]
+            })
+        }
+    }
+
+    /// With a target triple
+    ///
+    /// Fails if the triple is malformed or the
+    /// target it names isn't registered. Remember
+    /// to initialize the target first.
+    pub fn from_triple(triple: &str) -> Result<Target> {
+        unsafe {
+            let cstr = CString::new(triple)?;
+            let mut tpr: LLVMTargetRef = mem::zeroed();
+            let mut err: *mut c_char = mem::zeroed();
+            let flag = LLVMGetTargetFromTriple(cstr.as_ptr(), &mut tpr, &mut err);
+            if flag != 0 {
+                let message = if err.is_null() {
+                    String::new()
+                } else {
+                    take_message(err)
+                };
+                Err(Error::Target(TargetError {
+                    triple: triple.to_string(),
+                    message: message
+                }))
+            } else {
+                Ok(Target {
+                    data: tpr,
+                    buffers: vec![Buffers::A(cstr)]
+                })
             }
         }
     }
@@ -74,6 +121,16 @@ impl Target {
         }
     }
   
+    /// The target this library is running on
+    ///
+    /// Initializes the native target and looks
+    /// it up by the default target triple.
+    pub fn native() -> Result<Target> {
+        initialize_native()?;
+        let triple = get_local_triple();
+        Target::from_triple(&triple.to_string_lossy())
+    }
+
     /// Internal Method used for building Targets
     ///
     /// Generally this method is used internal to libraries
@@ -83,16 +140,188 @@ impl Target {
 }
 impl Default for Target {
 
+    /// The target this library is running on
+    ///
+    /// #Panic:
+    ///
+    /// This function will panic if the native
+    /// target isn't available, use `Target::native`
+    /// to handle that.
     fn default() -> Self {
-        unsafe{
-            Target {
-                data: LLVMGetFirstTarget(),
+        match Target::native() {
+            Ok(x) => x,
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
+
+/// Error from looking up a Target
+#[derive(Clone,Debug)]
+pub struct TargetError {
+    triple: String,
+    message: String
+}
+impl TargetError {
+
+    /// The triple that was looked up
+    pub fn triple(&self) -> &str {
+        &self.triple
+    }
+
+    /// The message the LLVM gave
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+impl fmt::Display for TargetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no target for `{}`: {}", self.triple, self.message)
+    }
+}
+impl error::Error for TargetError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Iterator over the registered targets
+///
+/// Returned by `targets`
+pub struct Targets {
+    next: LLVMTargetRef
+}
+impl Iterator for Targets {
+    type Item = Target;
+    fn next(&mut self) -> Option<Target> {
+        if self.next.is_null() {
+            return None;
+        }
+        unsafe {
+            let t = Target {
+                data: self.next,
                 buffers: Vec::with_capacity(0)
-            }
+            };
+            self.next = LLVMGetNextTarget(self.next);
+            Some(t)
+        }
+    }
+}
+
+/// Iterate over every registered target
+///
+/// Only targets that have been initialized
+/// are registered, see `initialize_native` and
+/// `initialize_all`.
+pub fn targets() -> Targets {
+    unsafe {
+        Targets {
+            next: LLVMGetFirstTarget()
+        }
+    }
+}
+
+/// Initialize the target the library is running on
+///
+/// Registers the native target, its asm printer,
+/// and its asm parser. Safe to call more then
+/// once, only the first call does anything.
+///
+/// Fails if the LLVM wasn't built with
+/// support for the native target.
+pub fn initialize_native() -> Result<()> {
+    static INIT: Once = Once::new();
+    static mut FAILED: bool = false;
+    unsafe {
+        INIT.call_once(|| {
+            FAILED = LLVM_InitializeNativeTarget() != 0
+                || LLVM_InitializeNativeAsmPrinter() != 0
+                || LLVM_InitializeNativeAsmParser() != 0;
+        });
+        if FAILED {
+            let triple = get_local_triple().to_string_lossy().into_owned();
+            return Err(Error::Target(TargetError {
+                triple: triple,
+                message: "native target isn't available".to_string()
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Initialize every target the LLVM was built with
+///
+/// Registers all target infos, targets, machine
+/// code layers, asm printers, asm parsers and
+/// disassemblers. Safe to call more then once,
+/// only the first call does anything.
+pub fn initialize_all() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| unsafe {
+        LLVM_InitializeAllTargetInfos();
+        LLVM_InitializeAllTargets();
+        LLVM_InitializeAllTargetMCs();
+        LLVM_InitializeAllAsmPrinters();
+        LLVM_InitializeAllAsmParsers();
+        LLVM_InitializeAllDisassemblers();
+    });
+}
+
+macro_rules! init_target {
+    ($feature: expr, $name: ident, $($init: ident),*) => {
+        /// Initialize a single architecture
+        ///
+        /// Safe to call more then once, only the
+        /// first call does anything.
+        #[cfg(feature = $feature)]
+        pub fn $name() {
+            static INIT: Once = Once::new();
+            INIT.call_once(|| unsafe {
+                $( $init(); )*
+            });
         }
     }
 }
 
+init_target!("aarch64", initialize_aarch64,
+    LLVMInitializeAArch64TargetInfo, LLVMInitializeAArch64Target, LLVMInitializeAArch64TargetMC,
+    LLVMInitializeAArch64AsmPrinter, LLVMInitializeAArch64AsmParser, LLVMInitializeAArch64Disassembler);
+init_target!("amdgpu", initialize_amdgpu,
+    LLVMInitializeAMDGPUTargetInfo, LLVMInitializeAMDGPUTarget, LLVMInitializeAMDGPUTargetMC,
+    LLVMInitializeAMDGPUAsmPrinter, LLVMInitializeAMDGPUAsmParser);
+init_target!("arm", initialize_arm,
+    LLVMInitializeARMTargetInfo, LLVMInitializeARMTarget, LLVMInitializeARMTargetMC,
+    LLVMInitializeARMAsmPrinter, LLVMInitializeARMAsmParser, LLVMInitializeARMDisassembler);
+init_target!("bpf", initialize_bpf,
+    LLVMInitializeBPFTargetInfo, LLVMInitializeBPFTarget, LLVMInitializeBPFTargetMC,
+    LLVMInitializeBPFAsmPrinter);
+init_target!("hexagon", initialize_hexagon,
+    LLVMInitializeHexagonTargetInfo, LLVMInitializeHexagonTarget, LLVMInitializeHexagonTargetMC,
+    LLVMInitializeHexagonAsmPrinter, LLVMInitializeHexagonDisassembler);
+init_target!("mips", initialize_mips,
+    LLVMInitializeMipsTargetInfo, LLVMInitializeMipsTarget, LLVMInitializeMipsTargetMC,
+    LLVMInitializeMipsAsmPrinter, LLVMInitializeMipsAsmParser, LLVMInitializeMipsDisassembler);
+init_target!("msp430", initialize_msp430,
+    LLVMInitializeMSP430TargetInfo, LLVMInitializeMSP430Target, LLVMInitializeMSP430TargetMC,
+    LLVMInitializeMSP430AsmPrinter);
+init_target!("nvptx", initialize_nvptx,
+    LLVMInitializeNVPTXTargetInfo, LLVMInitializeNVPTXTarget, LLVMInitializeNVPTXTargetMC,
+    LLVMInitializeNVPTXAsmPrinter);
+init_target!("powerpc", initialize_powerpc,
+    LLVMInitializePowerPCTargetInfo, LLVMInitializePowerPCTarget, LLVMInitializePowerPCTargetMC,
+    LLVMInitializePowerPCAsmPrinter, LLVMInitializePowerPCAsmParser, LLVMInitializePowerPCDisassembler);
+init_target!("sparc", initialize_sparc,
+    LLVMInitializeSparcTargetInfo, LLVMInitializeSparcTarget, LLVMInitializeSparcTargetMC,
+    LLVMInitializeSparcAsmPrinter, LLVMInitializeSparcAsmParser, LLVMInitializeSparcDisassembler);
+init_target!("systemz", initialize_systemz,
+    LLVMInitializeSystemZTargetInfo, LLVMInitializeSystemZTarget, LLVMInitializeSystemZTargetMC,
+    LLVMInitializeSystemZAsmPrinter, LLVMInitializeSystemZAsmParser, LLVMInitializeSystemZDisassembler);
+init_target!("x86", initialize_x86,
+    LLVMInitializeX86TargetInfo, LLVMInitializeX86Target, LLVMInitializeX86TargetMC,
+    LLVMInitializeX86AsmPrinter, LLVMInitializeX86AsmParser, LLVMInitializeX86Disassembler);
+init_target!("xcore", initialize_xcore,
+    LLVMInitializeXCoreTargetInfo, LLVMInitializeXCoreTarget, LLVMInitializeXCoreTargetMC,
+    LLVMInitializeXCoreAsmPrinter, LLVMInitializeXCoreDisassembler);
+
 /// Get target triple for _this_ target
 ///
 /// This returns the target triple this library is
@@ -197,6 +426,59 @@ impl Default for CodeModel {
     }
 }
 
+/// Output of code generation
+#[derive(Copy,Clone,Debug)]
+pub enum FileType {
+    /// Textual assembly
+    Assembly,
+    /// Native object file
+    Object
+}
+impl Into<LLVMCodeGenFileType> for FileType {
+    fn into(self) -> LLVMCodeGenFileType {
+        match self {
+            FileType::Assembly => LLVMCodeGenFileType::LLVMAssemblyFile,
+            FileType::Object => LLVMCodeGenFileType::LLVMObjectFile
+        }
+    }
+}
+impl Default for FileType {
+    #[inline(always)]
+    fn default() -> FileType {
+        FileType::Object
+    }
+}
+
+/// Error from code generation
+///
+/// Holds the message the LLVM gave
+#[derive(Clone,Debug)]
+pub struct CodeGenError {
+    message: String
+}
+impl CodeGenError {
+    fn new<S: Into<String>>(message: S) -> CodeGenError {
+        CodeGenError {
+            message: message.into()
+        }
+    }
+
+    /// The message the LLVM gave
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+impl fmt::Display for CodeGenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+impl error::Error for CodeGenError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
 /// Target Machine
 /// 
 /// Describe the physical machine that is being compiled too. 
@@ -221,33 +503,17 @@ impl Default for TargetMachine {
     /// It'll assume the CPU has NO special features
     /// 
     /// It'll assume the CPU is completely generic
+    ///
+    /// #Panic:
+    ///
+    /// This function will panic if the native
+    /// target isn't available, use
+    /// `BuildTargetMachine::new().build()` to
+    /// handle that.
     fn default() -> TargetMachine {
-        let cpu = CString::new("generic").unwrap();
-        let features = CString::new("").unwrap();
-        let t_ptr = Target::default();
-        let (tar,buffs) = unsafe{
-            t_ptr.split()
-        };
-        let triple = get_local_triple();
-        let tm_ptr = unsafe{
-            LLVMCreateTargetMachine(
-                tar, 
-                triple.as_ptr() as *const _, 
-                cpu.as_ptr() as *const _, 
-                features.as_ptr() as *const _,
-                CodeGenOptLevel::default().into(),
-                RelocMode::default().into(),
-                CodeModel::default().into()
-            )
-        };
-        let mut buffers = Vec::with_capacity(10);
-        buffers.push(Buffers::A(cpu));
-        buffers.push(Buffers::A(features));
-        buffers.push(Buffers::A(triple));
-        buffers.extend(buffs);
-        TargetMachine {
-            data: tm_ptr,
-            buffers: buffers
+        match BuildTargetMachine::new().build() {
+            Ok(x) => x,
+            Err(e) => panic!("{}", e)
         }
     }
 }
@@ -288,6 +554,13 @@ impl TargetMachine {
         }
     }
 
+    /// The data layout of this machine
+    pub fn target_data(&self) -> TargetData<'static> {
+        unsafe {
+            TargetData::from_raw(LLVMCreateTargetDataLayout(self.data), true)
+        }
+    }
+
     /// I don't know what this does
     pub fn set_asm_verbose(&mut self, flag: bool) {
         let llvmbool = if flag { 1 } else { 0 };
@@ -295,6 +568,56 @@ impl TargetMachine {
             LLVMSetTargetMachineAsmVerbosity(self.data,llvmbool);
         }
     }
+
+    /// Compile a Module to a file
+    ///
+    /// Writes either an object file or textual
+    /// assembly for this machine to `path`.
+    ///
+    /// The LLVM sets the module's data layout
+    /// and runs code generation passes over it.
+    pub fn emit_to_file<P: AsRef<Path>>(&self, module: &mut Module, path: P, kind: FileType) -> Result<()> {
+        let path = path_to_cstring(path.as_ref())?;
+        unsafe {
+            let mut err: *mut c_char = mem::zeroed();
+            let flag = LLVMTargetMachineEmitToFile(
+                self.data,
+                module.inner(),
+                path.as_ptr() as *mut c_char,
+                kind.into(),
+                &mut err);
+            if flag != 0 {
+                Err(Error::CodeGen(CodeGenError::new(take_message(err))))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Compile a Module into memory
+    ///
+    /// Returns either an object file or textual
+    /// assembly for this machine.
+    ///
+    /// The LLVM sets the module's data layout
+    /// and runs code generation passes over it.
+    pub fn emit_to_buffer(&self, module: &mut Module, kind: FileType) -> Result<Buffer> {
+        unsafe {
+            let mut err: *mut c_char = mem::zeroed();
+            let mut buf: LLVMMemoryBufferRef = mem::zeroed();
+            let flag = LLVMTargetMachineEmitToMemoryBuffer(
+                self.data,
+                module.inner(),
+                kind.into(),
+                &mut err,
+                &mut buf);
+            if flag != 0 {
+                Err(Error::CodeGen(CodeGenError::new(take_message(err))))
+            } else {
+                Ok(Buffer::from_raw(buf, Vec::with_capacity(0)))
+            }
+        }
+    }
  
 }
 
@@ -304,10 +627,14 @@ impl TargetMachine {
 /// TargetMachine. Effecively the function signature
 /// is massive so this makes construction more
 /// understandable.
+///
+/// Setters never fail, the first bad argument
+/// is held onto and returned from `build`.
 pub struct BuildTargetMachine {
+    error: Option<Error>,
     cpu: CString,
     features: CString,
-    target: Target,
+    target: Option<Target>,
     triple: CString,
     gen_opt: CodeGenOptLevel,
     reloc_mode: RelocMode,
@@ -319,12 +646,17 @@ impl BuildTargetMachine {
     ///
     /// On it's own calling new here and
     /// immidately building is no different
-    /// then just calling `TargetMachine::default()`
+    /// then just calling `TargetMachine::default()`,
+    /// except that it fails rather then panics.
+    ///
+    /// The native target is used unless
+    /// `set_target` is called.
     pub fn new() -> BuildTargetMachine {
         BuildTargetMachine {
+            error: None,
             cpu: CString::new("generic").unwrap(),
             features: CString::new("").unwrap(),
-            target: Target::default(),
+            target: None,
             triple: get_local_triple(),
             gen_opt: CodeGenOptLevel::default(),
             reloc_mode: RelocMode::default(),
@@ -333,23 +665,26 @@ impl BuildTargetMachine {
     }
 
     pub fn set_features<S: AsRef<str>>(&mut self, features: S) -> &mut Self {
-        let features = features.as_ref();
-        let cstr = CString::new(features).expect(NULLPTR);
-        self.features = cstr;
+        match CString::new(features.as_ref()) {
+            Ok(cstr) => self.features = cstr,
+            Err(e) => self.fail(e.into())
+        };
         self
     }
 
     pub fn set_cpu<S: AsRef<str>>(&mut self, cpu: S) -> &mut Self {
-        let cpu = cpu.as_ref();
-        let cstr = CString::new(cpu).expect(NULLPTR);
-        self.cpu = cstr;
+        match CString::new(cpu.as_ref()) {
+            Ok(cstr) => self.cpu = cstr,
+            Err(e) => self.fail(e.into())
+        };
         self
     }
 
     pub fn set_target_triple<S: AsRef<str>>(&mut self, triple: S) -> &mut Self {
-        let triple = triple.as_ref();
-        let cstr = CString::new(triple).expect(NULLPTR);
-        self.triple = cstr;
+        match CString::new(triple.as_ref()) {
+            Ok(cstr) => self.triple = cstr,
+            Err(e) => self.fail(e.into())
+        };
         self
     }
 
@@ -369,15 +704,45 @@ impl BuildTargetMachine {
     }
 
     pub fn set_target(&mut self, target: Target) -> &mut Self {
-        self.target = target;
+        self.target = Some(target);
         self
     }
 
+    /// Keep only the first error
+    fn fail(&mut self, err: Error) {
+        if self.error.is_none() {
+            self.error = Some(err);
+        }
+    }
+
+    /// Error naming the triple being built for
+    fn target_error(triple: &CStr, message: &str) -> Error {
+        Error::Target(TargetError {
+            triple: triple.to_string_lossy().into_owned(),
+            message: message.to_string()
+        })
+    }
+
     /// Complete the `TargetMachine` builder pattern    
-    pub fn build(self) -> TargetMachine {
+    ///
+    /// Fails with the first error a setter ran into,
+    /// if no target was set and the native one
+    /// isn't available, or if the LLVM can't create
+    /// a machine for the triple, cpu, and features.
+    pub fn build(self) -> Result<TargetMachine> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        let target = match self.target {
+            Some(x) => x,
+            None => Target::native()?
+        };
         let mut buffers = Vec::with_capacity(10);
         unsafe {
-            let (tar,buffs) = self.target.split();
+            let (tar,buffs) = target.split();
+            if tar.is_null() {
+                return Err(BuildTargetMachine::target_error(&self.triple, "target is null"));
+            }
             let tm_ptr = LLVMCreateTargetMachine(
                 tar,
                 self.triple.as_ptr() as *const _,
@@ -387,11 +752,254 @@ impl BuildTargetMachine {
                 self.reloc_mode.into(),
                 self.code_model.into()
             );
+            if tm_ptr.is_null() {
+                return Err(BuildTargetMachine::target_error(&self.triple, "the LLVM couldn't create a target machine"));
+            }
             buffers.extend(buffs);
             buffers.push(Buffers::A(self.cpu));
             buffers.push(Buffers::A(self.features));
             buffers.push(Buffers::A(self.triple));
-            TargetMachine::from_raw(tm_ptr, buffers)
+            Ok(TargetMachine::from_raw(tm_ptr, buffers))
+        }
+    }
+}
+
+/// Abstraction around llvm::DataLayout
+///
+/// Describes how types are laid out in memory
+/// on a target. The lifetime ties layouts
+/// borrowed from a `Module` to that module,
+/// ones created on their own are `'static`.
+pub struct TargetData<'a> {
+    data: LLVMTargetDataRef,
+    owned: bool,
+    marker: PhantomData<&'a ()>
+}
+impl<'a> Drop for TargetData<'a> {
+    fn drop(&mut self) {
+        if self.owned {
+            unsafe {
+                LLVMDisposeTargetData(self.data);
+            }
+        }
+    }
+}
+impl TargetData<'static> {
+
+    /// Parse a data layout string
+    ///
+    /// Such as `"e-m:e-i64:64-f80:128-n8:16:32:64-S128"`
+    ///
+    /// Only fails if `layout` contains a null byte.
+    /// The LLVM aborts the process on a malformed
+    /// layout rather then reporting an error, so
+    /// only pass layouts from a trusted source.
+    pub fn new<S: AsRef<str>>(layout: S) -> Result<TargetData<'static>> {
+        let layout = CString::new(layout.as_ref())?;
+        unsafe {
+            Ok(TargetData::from_raw(LLVMCreateTargetData(layout.as_ptr()), true))
+        }
+    }
+}
+impl<'a> TargetData<'a> {
+
+    /// Byte order of the target
+    pub fn byte_order(&self) -> ByteOrder {
+        unsafe {
+            LLVMByteOrder(self.data).into()
+        }
+    }
+
+    /// Size of a pointer in bytes
+    pub fn pointer_size(&self, address_space: u32) -> u32 {
+        unsafe {
+            LLVMPointerSizeForAS(self.data, address_space)
+        }
+    }
+
+    /// Integer type as wide as a pointer
+    pub fn int_ptr_type<'ctx>(&self, ctx: &'ctx Context, address_space: u32) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMIntPtrTypeForASInContext(ctx.inner(), self.data, address_space))
+        }
+    }
+
+    /// Size of `ty` in bits
+    ///
+    /// Returns `None` if `ty` isn't sized.
+    pub fn size_of_bits(&self, ty: Type) -> Option<u64> {
+        if !ty.is_sized() {
+            return None;
+        }
+        unsafe {
+            Some(LLVMSizeOfTypeInBits(self.data, ty.inner()))
+        }
+    }
+
+    /// Bytes written when storing `ty`
+    ///
+    /// Returns `None` if `ty` isn't sized.
+    pub fn store_size(&self, ty: Type) -> Option<u64> {
+        if !ty.is_sized() {
+            return None;
+        }
+        unsafe {
+            Some(LLVMStoreSizeOfType(self.data, ty.inner()))
+        }
+    }
+
+    /// Bytes between consecutive `ty` in an
+    /// array, padding included
+    ///
+    /// Returns `None` if `ty` isn't sized.
+    pub fn abi_size(&self, ty: Type) -> Option<u64> {
+        if !ty.is_sized() {
+            return None;
+        }
+        unsafe {
+            Some(LLVMABISizeOfType(self.data, ty.inner()))
+        }
+    }
+
+    /// Minimum alignment of `ty` in bytes
+    ///
+    /// Returns `None` if `ty` isn't sized.
+    pub fn abi_alignment(&self, ty: Type) -> Option<u32> {
+        if !ty.is_sized() {
+            return None;
+        }
+        unsafe {
+            Some(LLVMABIAlignmentOfType(self.data, ty.inner()))
+        }
+    }
+
+    /// Alignment of `ty` on the call stack in bytes
+    ///
+    /// Returns `None` if `ty` isn't sized.
+    pub fn call_frame_alignment(&self, ty: Type) -> Option<u32> {
+        if !ty.is_sized() {
+            return None;
+        }
+        unsafe {
+            Some(LLVMCallFrameAlignmentOfType(self.data, ty.inner()))
+        }
+    }
+
+    /// Alignment the target would like `ty` to
+    /// have in bytes
+    ///
+    /// Returns `None` if `ty` isn't sized.
+    pub fn preferred_alignment(&self, ty: Type) -> Option<u32> {
+        if !ty.is_sized() {
+            return None;
+        }
+        unsafe {
+            Some(LLVMPreferredAlignmentOfType(self.data, ty.inner()))
+        }
+    }
+
+    /// Byte offset of field `idx` of the struct `ty`
+    ///
+    /// Returns `None` if `ty` isn't a sized struct
+    /// or has no such field.
+    pub fn offset_of_element(&self, ty: Type, idx: u32) -> Option<u64> {
+        if ty.kind() != TypeKind::Struct || !ty.is_sized() {
+            return None;
+        }
+        unsafe {
+            if idx >= LLVMCountStructElementTypes(ty.inner()) {
+                return None;
+            }
+            Some(LLVMOffsetOfElement(self.data, ty.inner(), idx))
+        }
+    }
+
+    /// Index of the field of the struct `ty` which
+    /// holds byte `offset`
+    ///
+    /// Returns `None` if `ty` isn't a sized struct
+    /// or `offset` is past its end.
+    pub fn element_at_offset(&self, ty: Type, offset: u64) -> Option<u32> {
+        if ty.kind() != TypeKind::Struct || ty.is_opaque() {
+            return None;
         }
+        match self.abi_size(ty) {
+            Some(size) if offset < size => {},
+            _ => return None
+        }
+        unsafe {
+            Some(LLVMElementAtOffset(self.data, ty.inner(), offset))
+        }
+    }
+
+    /// Method internal to the library.
+    ///
+    /// Only `owned` layouts are disposed of.
+    pub unsafe fn from_raw(data: LLVMTargetDataRef, owned: bool) -> TargetData<'a> {
+        TargetData {
+            data: data,
+            owned: owned,
+            marker: PhantomData
+        }
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&self) -> LLVMTargetDataRef {
+        self.data
+    }
+}
+impl<'a> fmt::Display for TargetData<'a> {
+    /// Prints the data layout string
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = unsafe {
+            take_message(LLVMCopyStringRepOfTargetData(self.data))
+        };
+        f.write_str(&s)
+    }
+}
+impl<'a> fmt::Debug for TargetData<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Byte order of a target
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum ByteOrder {
+    BigEndian,
+    LittleEndian
+}
+impl From<LLVMByteOrdering> for ByteOrder {
+    fn from(order: LLVMByteOrdering) -> ByteOrder {
+        match order {
+            LLVMByteOrdering::LLVMBigEndian => ByteOrder::BigEndian,
+            LLVMByteOrdering::LLVMLittleEndian => ByteOrder::LittleEndian
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn struct_layout() {
+        let ctx = Context::new();
+        let data = TargetData::new("e-i64:64").unwrap();
+        let ty = Type::structure(&ctx, &[Type::int(&ctx, 8), Type::int(&ctx, 64)], false);
+        assert_eq!(data.abi_size(ty), Some(16));
+        assert_eq!(data.offset_of_element(ty, 1), Some(8));
+        assert_eq!(data.element_at_offset(ty, 8), Some(1));
+    }
+
+    #[test]
+    fn opaque_struct_layout() {
+        let ctx = Context::new();
+        let data = TargetData::new("e-i64:64").unwrap();
+        let ty = Type::named_struct(&ctx, "opaque").unwrap();
+        assert_eq!(data.abi_size(ty), None);
+        assert_eq!(data.offset_of_element(ty, 0), None);
+        assert_eq!(data.element_at_offset(ty, 0), None);
     }
 }
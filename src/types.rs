@@ -0,0 +1,369 @@
+
+use llvm_sys::*;
+use llvm_sys::prelude::*;
+use llvm_sys::core::*;
+
+use std::ffi::{CString,CStr};
+use std::marker::PhantomData;
+use std::fmt;
+
+use super::context::{Context,Brand};
+use super::take_message;
+use super::error::Result;
+
+/// Abstraction around llvm::Type
+///
+/// Types are uniqued and owned by the `Context`
+/// that created them. This is just a handle
+/// to one.
+#[derive(Copy,Clone)]
+pub struct Type<'ctx> {
+    data: LLVMTypeRef,
+    context: Brand<'ctx>
+}
+impl<'ctx> Type<'ctx> {
+
+    /// Integer type `iN` where N is `bits`
+    pub fn int(ctx: &'ctx Context, bits: u32) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMIntTypeInContext(ctx.inner(), bits))
+        }
+    }
+
+    /// The `void` type
+    pub fn void(ctx: &'ctx Context) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMVoidTypeInContext(ctx.inner()))
+        }
+    }
+
+    /// 16bit floating point
+    pub fn half(ctx: &'ctx Context) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMHalfTypeInContext(ctx.inner()))
+        }
+    }
+
+    /// 32bit floating point
+    pub fn float(ctx: &'ctx Context) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMFloatTypeInContext(ctx.inner()))
+        }
+    }
+
+    /// 64bit floating point
+    pub fn double(ctx: &'ctx Context) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMDoubleTypeInContext(ctx.inner()))
+        }
+    }
+
+    /// 128bit floating point
+    pub fn fp128(ctx: &'ctx Context) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMFP128TypeInContext(ctx.inner()))
+        }
+    }
+
+    /// x87's 80bit floating point
+    pub fn x86_fp80(ctx: &'ctx Context) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMX86FP80TypeInContext(ctx.inner()))
+        }
+    }
+
+    /// PowerPC's double-double 128bit floating point
+    pub fn ppc_fp128(ctx: &'ctx Context) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMPPCFP128TypeInContext(ctx.inner()))
+        }
+    }
+
+    /// Pointer to this type within `address_space`
+    ///
+    /// Address space 0 is the normal one.
+    pub fn pointer(&self, address_space: u32) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMPointerType(self.data, address_space))
+        }
+    }
+
+    /// Array of `len` of this type
+    pub fn array(&self, len: u32) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMArrayType(self.data, len))
+        }
+    }
+
+    /// SIMD vector of `len` of this type
+    pub fn vector(&self, len: u32) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMVectorType(self.data, len))
+        }
+    }
+
+    /// Literal (unnamed) struct type
+    ///
+    /// Literal structs are uniqued by their fields,
+    /// so two calls with the same fields return the
+    /// same type.
+    pub fn structure(ctx: &'ctx Context, fields: &[Type<'ctx>], packed: bool) -> Type<'ctx> {
+        let mut fields = raw_types(fields);
+        unsafe {
+            let len = fields.len() as u32;
+            Type::from_raw(LLVMStructTypeInContext(ctx.inner(), fields.as_mut_ptr(), len, packed as LLVMBool))
+        }
+    }
+
+    /// Named struct type
+    ///
+    /// The struct starts out opaque, give it
+    /// fields with `set_body`. If the name is
+    /// already taken the LLVM will rename it.
+    pub fn named_struct<S: AsRef<str>>(ctx: &'ctx Context, name: S) -> Result<Type<'ctx>> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            Ok(Type::from_raw(LLVMStructCreateNamed(ctx.inner(), name.as_ptr())))
+        }
+    }
+
+    /// Set the fields of a named struct
+    pub fn set_body(&self, fields: &[Type<'ctx>], packed: bool) {
+        let mut fields = raw_types(fields);
+        unsafe {
+            let len = fields.len() as u32;
+            LLVMStructSetBody(self.data, fields.as_mut_ptr(), len, packed as LLVMBool);
+        }
+    }
+
+    /// Function type
+    ///
+    /// `var_args` makes this a C style `...` function
+    pub fn function(ret: Type<'ctx>, params: &[Type<'ctx>], var_args: bool) -> Type<'ctx> {
+        let mut params = raw_types(params);
+        unsafe {
+            let len = params.len() as u32;
+            Type::from_raw(LLVMFunctionType(ret.inner(), params.as_mut_ptr(), len, var_args as LLVMBool))
+        }
+    }
+
+    /// What kind of type this is
+    pub fn kind(&self) -> TypeKind {
+        unsafe {
+            LLVMGetTypeKind(self.data).into()
+        }
+    }
+
+    /// Width of an integer type
+    ///
+    /// Returns `None` for non-integer types
+    pub fn int_width(&self) -> Option<u32> {
+        match self.kind() {
+            TypeKind::Integer => unsafe { Some(LLVMGetIntTypeWidth(self.data)) },
+            _ => None
+        }
+    }
+
+    /// The type pointed to, or contained in an array or vector
+    ///
+    /// Returns `None` for all other types
+    pub fn element_type(&self) -> Option<Type<'ctx>> {
+        match self.kind() {
+            TypeKind::Pointer |
+            TypeKind::Array |
+            TypeKind::Vector => unsafe { Some(Type::from_raw(LLVMGetElementType(self.data))) },
+            _ => None
+        }
+    }
+
+    /// Length of an array type
+    pub fn array_len(&self) -> Option<u32> {
+        match self.kind() {
+            TypeKind::Array => unsafe { Some(LLVMGetArrayLength(self.data)) },
+            _ => None
+        }
+    }
+
+    /// Length of a vector type
+    pub fn vector_len(&self) -> Option<u32> {
+        match self.kind() {
+            TypeKind::Vector => unsafe { Some(LLVMGetVectorSize(self.data)) },
+            _ => None
+        }
+    }
+
+    /// Address space of a pointer type
+    pub fn address_space(&self) -> Option<u32> {
+        match self.kind() {
+            TypeKind::Pointer => unsafe { Some(LLVMGetPointerAddressSpace(self.data)) },
+            _ => None
+        }
+    }
+
+    /// Fields of a struct type
+    ///
+    /// Opaque structs and non-struct types have no fields
+    pub fn struct_fields(&self) -> Vec<Type<'ctx>> {
+        match self.kind() {
+            TypeKind::Struct => unsafe {
+                let len = LLVMCountStructElementTypes(self.data) as usize;
+                let mut v: Vec<LLVMTypeRef> = Vec::with_capacity(len);
+                LLVMGetStructElementTypes(self.data, v.as_mut_ptr());
+                v.set_len(len);
+                v.into_iter().map(|t| Type::from_raw(t)).collect()
+            },
+            _ => Vec::with_capacity(0)
+        }
+    }
+
+    /// Name of a named struct
+    ///
+    /// Literal structs and non-struct types have no name
+    pub fn struct_name(&self) -> Option<CString> {
+        match self.kind() {
+            TypeKind::Struct => unsafe {
+                let ptr = LLVMGetStructName(self.data);
+                if ptr.is_null() {
+                    None
+                } else {
+                    Some(CStr::from_ptr(ptr).to_owned())
+                }
+            },
+            _ => None
+        }
+    }
+
+    /// Is this a packed struct
+    pub fn is_packed(&self) -> bool {
+        match self.kind() {
+            TypeKind::Struct => unsafe { LLVMIsPackedStruct(self.data) != 0 },
+            _ => false
+        }
+    }
+
+    /// Is this a struct without a body
+    pub fn is_opaque(&self) -> bool {
+        match self.kind() {
+            TypeKind::Struct => unsafe { LLVMIsOpaqueStruct(self.data) != 0 },
+            _ => false
+        }
+    }
+
+    /// Return type of a function type
+    pub fn return_type(&self) -> Option<Type<'ctx>> {
+        match self.kind() {
+            TypeKind::Function => unsafe { Some(Type::from_raw(LLVMGetReturnType(self.data))) },
+            _ => None
+        }
+    }
+
+    /// Parameter types of a function type
+    pub fn param_types(&self) -> Vec<Type<'ctx>> {
+        match self.kind() {
+            TypeKind::Function => unsafe {
+                let len = LLVMCountParamTypes(self.data) as usize;
+                let mut v: Vec<LLVMTypeRef> = Vec::with_capacity(len);
+                LLVMGetParamTypes(self.data, v.as_mut_ptr());
+                v.set_len(len);
+                v.into_iter().map(|t| Type::from_raw(t)).collect()
+            },
+            _ => Vec::with_capacity(0)
+        }
+    }
+
+    /// Is this a function type taking C style var args
+    pub fn is_var_arg(&self) -> bool {
+        match self.kind() {
+            TypeKind::Function => unsafe { LLVMIsFunctionVarArg(self.data) != 0 },
+            _ => false
+        }
+    }
+
+    /// Does this type have a size
+    ///
+    /// Things like `void`, functions, and opaque
+    /// structs don't.
+    pub fn is_sized(&self) -> bool {
+        unsafe {
+            LLVMTypeIsSized(self.data) != 0
+        }
+    }
+
+    /// Method internal to the library.
+    pub unsafe fn from_raw(data: LLVMTypeRef) -> Type<'ctx> {
+        Type {
+            data: data,
+            context: PhantomData
+        }
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&self) -> LLVMTypeRef {
+        self.data
+    }
+}
+impl<'ctx> fmt::Display for Type<'ctx> {
+    /// Prints the textual IR
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = unsafe {
+            take_message(LLVMPrintTypeToString(self.data))
+        };
+        f.write_str(&s)
+    }
+}
+impl<'ctx> fmt::Debug for Type<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Converts a slice of types for passing to the LLVM
+fn raw_types<'ctx>(types: &[Type<'ctx>]) -> Vec<LLVMTypeRef> {
+    types.iter().map(|t| unsafe { t.inner() }).collect()
+}
+
+/// The different kinds of types
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum TypeKind {
+    Void,
+    Half,
+    Float,
+    Double,
+    X86FP80,
+    FP128,
+    PPCFP128,
+    Label,
+    Integer,
+    Function,
+    Struct,
+    Array,
+    Pointer,
+    Vector,
+    Metadata,
+    X86MMX,
+    Token
+}
+impl From<LLVMTypeKind> for TypeKind {
+    fn from(kind: LLVMTypeKind) -> TypeKind {
+        match kind {
+            LLVMTypeKind::LLVMVoidTypeKind => TypeKind::Void,
+            LLVMTypeKind::LLVMHalfTypeKind => TypeKind::Half,
+            LLVMTypeKind::LLVMFloatTypeKind => TypeKind::Float,
+            LLVMTypeKind::LLVMDoubleTypeKind => TypeKind::Double,
+            LLVMTypeKind::LLVMX86_FP80TypeKind => TypeKind::X86FP80,
+            LLVMTypeKind::LLVMFP128TypeKind => TypeKind::FP128,
+            LLVMTypeKind::LLVMPPC_FP128TypeKind => TypeKind::PPCFP128,
+            LLVMTypeKind::LLVMLabelTypeKind => TypeKind::Label,
+            LLVMTypeKind::LLVMIntegerTypeKind => TypeKind::Integer,
+            LLVMTypeKind::LLVMFunctionTypeKind => TypeKind::Function,
+            LLVMTypeKind::LLVMStructTypeKind => TypeKind::Struct,
+            LLVMTypeKind::LLVMArrayTypeKind => TypeKind::Array,
+            LLVMTypeKind::LLVMPointerTypeKind => TypeKind::Pointer,
+            LLVMTypeKind::LLVMVectorTypeKind => TypeKind::Vector,
+            LLVMTypeKind::LLVMMetadataTypeKind => TypeKind::Metadata,
+            LLVMTypeKind::LLVMX86_MMXTypeKind => TypeKind::X86MMX,
+            LLVMTypeKind::LLVMTokenTypeKind => TypeKind::Token
+        }
+    }
+}
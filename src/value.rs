@@ -0,0 +1,161 @@
+
+use llvm_sys::*;
+use llvm_sys::prelude::*;
+use llvm_sys::core::*;
+
+use std::ffi::{CString,CStr};
+use std::marker::PhantomData;
+use std::fmt;
+
+use super::context::Brand;
+use super::types::Type;
+use super::take_message;
+use super::error::Result;
+
+/// Abstraction around llvm::Value
+///
+/// Values are everything from constants, to
+/// function arguments, to the instructions
+/// emitted by a `Builder`. This is just a handle,
+/// the Value itself is owned by the LLVM and
+/// lives as long as the `Context` it came from.
+#[derive(Copy,Clone)]
+pub struct Value<'ctx> {
+    data: LLVMValueRef,
+    context: Brand<'ctx>
+}
+impl<'ctx> Value<'ctx> {
+
+    /// Get the type of this value
+    pub fn get_type(&self) -> Type<'ctx> {
+        unsafe {
+            Type::from_raw(LLVMTypeOf(self.data))
+        }
+    }
+
+    /// Get the name of this item
+    ///
+    /// Unnamed values return an empty string. The
+    /// name is copied as `set_name` may free it.
+    pub fn get_name(&self) -> CString {
+        unsafe {
+            CStr::from_ptr(LLVMGetValueName(self.data)).to_owned()
+        }
+    }
+
+    /// Set the name of this item
+    ///
+    /// The LLVM copies the name, and may rename
+    /// it if it collides with another value.
+    pub fn set_name<S: AsRef<str>>(&mut self, name: S) -> Result<()> {
+        let name = CString::new(name.as_ref())?;
+        unsafe {
+            LLVMSetValueName(self.data, name.as_ptr());
+        }
+        Ok(())
+    }
+
+    /// Method internal to the library.
+    pub unsafe fn from_raw(data: LLVMValueRef) -> Value<'ctx> {
+        Value {
+            data: data,
+            context: PhantomData
+        }
+    }
+
+    /// Allows access to inner data field
+    /// for within library functions
+    pub unsafe fn inner(&self) -> LLVMValueRef {
+        self.data
+    }
+}
+impl<'ctx> fmt::Display for Value<'ctx> {
+    /// Prints the textual IR
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = unsafe {
+            take_message(LLVMPrintValueToString(self.data))
+        };
+        f.write_str(&s)
+    }
+}
+impl<'ctx> fmt::Debug for Value<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Converts a slice of values for passing to the LLVM
+pub(crate) fn raw_values<'ctx>(vals: &[Value<'ctx>]) -> Vec<LLVMValueRef> {
+    vals.iter().map(|v| unsafe { v.inner() }).collect()
+}
+
+/// Linkage types
+///
+/// Describes how a global value (function or
+/// global variable) is seen by the linker.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum Linkage {
+    External,
+    AvailableExternally,
+    LinkOnceAny,
+    LinkOnceODR,
+    LinkOnceODRAutoHide,
+    WeakAny,
+    WeakODR,
+    Appending,
+    Internal,
+    Private,
+    DLLImport,
+    DLLExport,
+    ExternalWeak,
+    Ghost,
+    Common,
+    LinkerPrivate,
+    LinkerPrivateWeak
+}
+impl Into<LLVMLinkage> for Linkage {
+    fn into(self) -> LLVMLinkage {
+        match self {
+            Linkage::External => LLVMLinkage::LLVMExternalLinkage,
+            Linkage::AvailableExternally => LLVMLinkage::LLVMAvailableExternallyLinkage,
+            Linkage::LinkOnceAny => LLVMLinkage::LLVMLinkOnceAnyLinkage,
+            Linkage::LinkOnceODR => LLVMLinkage::LLVMLinkOnceODRLinkage,
+            Linkage::LinkOnceODRAutoHide => LLVMLinkage::LLVMLinkOnceODRAutoHideLinkage,
+            Linkage::WeakAny => LLVMLinkage::LLVMWeakAnyLinkage,
+            Linkage::WeakODR => LLVMLinkage::LLVMWeakODRLinkage,
+            Linkage::Appending => LLVMLinkage::LLVMAppendingLinkage,
+            Linkage::Internal => LLVMLinkage::LLVMInternalLinkage,
+            Linkage::Private => LLVMLinkage::LLVMPrivateLinkage,
+            Linkage::DLLImport => LLVMLinkage::LLVMDLLImportLinkage,
+            Linkage::DLLExport => LLVMLinkage::LLVMDLLExportLinkage,
+            Linkage::ExternalWeak => LLVMLinkage::LLVMExternalWeakLinkage,
+            Linkage::Ghost => LLVMLinkage::LLVMGhostLinkage,
+            Linkage::Common => LLVMLinkage::LLVMCommonLinkage,
+            Linkage::LinkerPrivate => LLVMLinkage::LLVMLinkerPrivateLinkage,
+            Linkage::LinkerPrivateWeak => LLVMLinkage::LLVMLinkerPrivateWeakLinkage
+        }
+    }
+}
+impl From<LLVMLinkage> for Linkage {
+    fn from(linkage: LLVMLinkage) -> Linkage {
+        match linkage {
+            LLVMLinkage::LLVMExternalLinkage => Linkage::External,
+            LLVMLinkage::LLVMAvailableExternallyLinkage => Linkage::AvailableExternally,
+            LLVMLinkage::LLVMLinkOnceAnyLinkage => Linkage::LinkOnceAny,
+            LLVMLinkage::LLVMLinkOnceODRLinkage => Linkage::LinkOnceODR,
+            LLVMLinkage::LLVMLinkOnceODRAutoHideLinkage => Linkage::LinkOnceODRAutoHide,
+            LLVMLinkage::LLVMWeakAnyLinkage => Linkage::WeakAny,
+            LLVMLinkage::LLVMWeakODRLinkage => Linkage::WeakODR,
+            LLVMLinkage::LLVMAppendingLinkage => Linkage::Appending,
+            LLVMLinkage::LLVMInternalLinkage => Linkage::Internal,
+            LLVMLinkage::LLVMPrivateLinkage => Linkage::Private,
+            LLVMLinkage::LLVMDLLImportLinkage => Linkage::DLLImport,
+            LLVMLinkage::LLVMDLLExportLinkage => Linkage::DLLExport,
+            LLVMLinkage::LLVMExternalWeakLinkage => Linkage::ExternalWeak,
+            LLVMLinkage::LLVMGhostLinkage => Linkage::Ghost,
+            LLVMLinkage::LLVMCommonLinkage => Linkage::Common,
+            LLVMLinkage::LLVMLinkerPrivateLinkage => Linkage::LinkerPrivate,
+            LLVMLinkage::LLVMLinkerPrivateWeakLinkage => Linkage::LinkerPrivateWeak
+        }
+    }
+}